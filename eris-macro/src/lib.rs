@@ -16,7 +16,7 @@ pub fn eris_elements(input: proc_macro::TokenStream) -> proc_macro::TokenStream
     toks.into()
 }
 
-#[proc_macro_derive(ErisValidEnum, attributes(emoji))]
+#[proc_macro_derive(ErisValidEnum, attributes(emoji, hidden))]
 pub fn eris_valid_enum(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let ast = syn::parse_macro_input!(input as syn::DeriveInput);
     let toks = player_actions::eris_valid_enum(&ast);
@@ -52,7 +52,7 @@ pub fn eris_mob(ast: &DeriveInput) -> TokenStream2 {
                 let maximum_weight = choices.iter().map(|&(_, grade)| grade as u32).max().unwrap();
                 let weights: Vec<u32> = choices.iter().map(|&(mob, variant)| maximum_weight / variant as u32).collect();
                 let dist = rand::distributions::WeightedIndex::new(&weights).unwrap();
-                let chosen_index = dist.sample(&mut rand::thread_rng());
+                let chosen_index = dist.sample(rng);
                 choices[chosen_index].0
         }
     }
@@ -146,6 +146,42 @@ impl Parse for VariantAttribute {
     }
 }
 
+/// Parses an entry of `actions`, `Action:weight@condition` where the weight and the condition
+/// (`opening`, `enraged` or `hp<N`) are optional, into the action and its rule.
+fn action_rule(entry: &str) -> (String, TokenStream2) {
+    let (action, condition) = match entry.split_once('@') {
+        Some((action, condition)) => (action, Some(condition.trim())),
+        None => (entry, None),
+    };
+    let (action, weight) = match action.split_once(':') {
+        Some((action, weight)) => (
+            action.trim(),
+            weight
+                .trim()
+                .parse::<u32>()
+                .expect("Action weight must be a number"),
+        ),
+        None => (action.trim(), 1),
+    };
+    let condition = match condition {
+        None => quote! { crate::enemy::ActionCondition::Always },
+        Some("opening") => quote! { crate::enemy::ActionCondition::Opening },
+        Some("enraged") => quote! { crate::enemy::ActionCondition::Enraged },
+        Some(condition) => {
+            let percent: u32 = condition
+                .strip_prefix("hp<")
+                .and_then(|percent| percent.parse().ok())
+                .expect("Action condition must be opening, enraged or hp<N");
+            quote! { crate::enemy::ActionCondition::HpBelow(#percent) }
+        }
+    };
+    let ident = TokenStream2::from_str(action).unwrap();
+    (
+        action.to_string(),
+        quote! { crate::enemy::MobActionRule { action: #ident, weight: #weight, condition: #condition } },
+    )
+}
+
 fn parse_application_attributes(attrs: &[syn::Attribute]) -> Vec<VariantAttribute> {
     attrs
         .iter()
@@ -168,6 +204,7 @@ pub(crate) fn eris_flat_mob(ast: &DeriveInput) -> TokenStream2 {
     let mut alignment_sets = Vec::new();
     let mut vulnerability_sets = Vec::new();
     let mut actions_sets = Vec::new();
+    let mut rules_sets = Vec::new();
 
     for variant in variants {
         let attrs = parse_application_attributes(&variant.attrs);
@@ -178,15 +215,26 @@ pub(crate) fn eris_flat_mob(ast: &DeriveInput) -> TokenStream2 {
             let alignment = attr.alignment;
 
             let vulnerability = attr.vulnerability;
-            let actions: Vec<_> = attr
-                .actions
+            let mut names: Vec<String> = vec![];
+            let mut rules = vec![];
+            for entry in attr.actions.iter() {
+                let (name, rule) = action_rule(entry);
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+                rules.push(rule);
+            }
+            let actions: Vec<_> = names
                 .iter()
                 .map(|x| TokenStream2::from_str(&x).unwrap())
                 .collect();
 
-            choices.push(
-                quote! { choices.extend(vec![(#name::#variant_name, crate::enemy::MobGrade::#grade)]); },
-            );
+            // Bosses wait at the end of a dungeon run instead of roaming the zones
+            if attr.grade != "Boss" {
+                choices.push(
+                    quote! { choices.extend(vec![(#name::#variant_name, crate::enemy::MobGrade::#grade)]); },
+                );
+            }
 
             grade_sets.push(quote! { #name::#variant_name => #grade});
 
@@ -205,6 +253,7 @@ pub(crate) fn eris_flat_mob(ast: &DeriveInput) -> TokenStream2 {
             }
 
             actions_sets.push(quote! { #name::#variant_name => vec![#(#actions),*] });
+            rules_sets.push(quote! { #name::#variant_name => vec![#(#rules),*] });
         }
     }
 
@@ -215,7 +264,7 @@ pub(crate) fn eris_flat_mob(ast: &DeriveInput) -> TokenStream2 {
                 let maximum_weight = choices.iter().map(|&(_, grade)| grade as u32).max().unwrap();
                 let weights: Vec<u32> = choices.iter().map(|&(mob, variant)| maximum_weight / variant as u32).collect();
                 let dist = rand::distributions::WeightedIndex::new(&weights).unwrap();
-                let chosen_index = dist.sample(&mut rand::thread_rng());
+                let chosen_index = dist.sample(rng);
                 choices[chosen_index].0
         }
     }
@@ -246,6 +295,12 @@ pub(crate) fn eris_flat_mob(ast: &DeriveInput) -> TokenStream2 {
                     _ => None
                 }
             }
+            fn action_rules(&self) -> Vec<crate::enemy::MobActionRule> {
+                use crate::skill::MobAction::*;
+                match self {
+                    #(#rules_sets,)*
+                }
+            }
         }
     };
     tokens.into()
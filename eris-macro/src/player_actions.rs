@@ -165,8 +165,11 @@ pub fn eris_valid_enum(ast: &DeriveInput) -> TokenStream2 {
     };
 
     let mut ee = vec![];
-    // Iterate through the attributes of each variant
+    // Iterate through the attributes of each variant, `#[hidden]` ones are not listed
     for variant in variants {
+        if variant.attrs.iter().any(|attr| attr.path().is_ident("hidden")) {
+            continue;
+        }
         ee.push(variant.ident.clone());
     }
 
@@ -7,11 +7,28 @@ use crate::BattleInfo;
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct BattleResult {
     pub result: Vec<BattleInfo>,
+    /// A party raid, with one result per member that fought.
+    #[serde(default)]
+    pub raid: bool,
 }
 
 impl BattleResult {
     pub fn new(result: Vec<BattleInfo>) -> Self {
-        Self { result }
+        Self {
+            result,
+            raid: false,
+        }
+    }
+
+    pub fn raid(result: Vec<BattleInfo>) -> Self {
+        Self { result, raid: true }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self.raid {
+            true => "Raid Results:",
+            false => "Battle Results:",
+        }
     }
 
     pub fn append_result(&mut self, result: BattleInfo) {
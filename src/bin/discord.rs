@@ -52,13 +52,27 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             command::help(),
             command::create(),
             command::character_trait(),
+            command::boost(),
+            command::respec(),
             command::delete(),
             command::me(),
+            command::rest(),
             command::skill(),
             command::battle(),
             command::items(),
             command::sell(),
             command::equip(),
+            command::inspect(),
+            command::shop(),
+            command::gamble(),
+            command::trade(),
+            command::party(),
+            command::zone(),
+            command::target(),
+            command::dungeon(),
+            command::promote(),
+            command::multiclass(),
+            command::loadout(),
             command::sum(),
         ],
         prefix_options: poise::PrefixFrameworkOptions {
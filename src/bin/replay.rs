@@ -0,0 +1,31 @@
+use std::{path::Path, process::ExitCode};
+
+use carrion_eris::replay::BattleLog;
+
+fn main() -> ExitCode {
+    let Some(path) = std::env::args().nth(1) else {
+        eprintln!("Usage: replay <battle log>");
+        return ExitCode::FAILURE;
+    };
+
+    let log = match BattleLog::read(Path::new(&path)) {
+        Ok(log) => log,
+        Err(e) => {
+            eprintln!("Failed to read {path}: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match log.verify() {
+        Ok(replayed) => {
+            println!("{replayed}");
+            println!("The replay matches the log");
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            println!("{log}");
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
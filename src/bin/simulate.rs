@@ -0,0 +1,77 @@
+use std::process::ExitCode;
+
+use carrion_eris::class::Classes;
+use carrion_eris::enemy::Mob;
+use carrion_eris::simulation::{simulate, MatchupReport, SimulationConfig};
+use carrion_eris::skill::Skill;
+
+const USAGE: &str = "Usage: simulate [--fights N] [--seed N] [--levels 1,10,50] [--class warrior] [--skill slash] [--mob orc] [--format csv|json]";
+
+enum Format {
+    Csv,
+    Json,
+}
+
+fn parse_args(args: &[String]) -> Result<(SimulationConfig, Format), String> {
+    let mut config = SimulationConfig::default();
+    let mut format = Format::Csv;
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        let value = args
+            .next()
+            .ok_or_else(|| format!("Missing value for {flag}"))?;
+        match flag.as_str() {
+            "--fights" => config.fights = value.parse().map_err(|e| format!("{flag}: {e}"))?,
+            "--seed" => config.seed = value.parse().map_err(|e| format!("{flag}: {e}"))?,
+            "--levels" => {
+                config.levels = value
+                    .split(',')
+                    .map(|level| level.trim().parse())
+                    .collect::<Result<_, _>>()
+                    .map_err(|e| format!("{flag}: {e}"))?
+            }
+            "--class" => config.classes = vec![Classes::try_from(value.to_string())?],
+            "--skill" => config.skills = vec![Skill::try_from(value.to_string())?],
+            "--mob" => config.mobs = vec![Mob::try_from(value.to_string())?],
+            "--format" => {
+                format = match value.as_str() {
+                    "csv" => Format::Csv,
+                    "json" => Format::Json,
+                    other => return Err(format!("Unknown format {other}")),
+                }
+            }
+            other => return Err(format!("Unknown flag {other}")),
+        }
+    }
+    Ok((config, format))
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (config, format) = match parse_args(&args) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("{e}");
+            eprintln!("{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let reports = simulate(&config);
+    match format {
+        Format::Csv => {
+            println!("{}", MatchupReport::CSV_HEADER);
+            for report in &reports {
+                println!("{}", report.csv_row());
+            }
+        }
+        Format::Json => match serde_json::to_string_pretty(&reports) {
+            Ok(json) => println!("{json}"),
+            Err(e) => {
+                eprintln!("Failed to serialize reports: {e}");
+                return ExitCode::FAILURE;
+            }
+        },
+    }
+    ExitCode::SUCCESS
+}
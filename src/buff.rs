@@ -0,0 +1,127 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+/// Rounds a buff lasts after it is cast.
+pub const BUFF_TURNS: u32 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BuffKind {
+    /// Extra armor against physical hits.
+    Armor,
+    /// Extra universal resistance against everything else.
+    Ward,
+    Dodge,
+    /// Extra action points.
+    Haste,
+    /// Extra damage multiplier, in percent.
+    Empower,
+}
+
+/// A timed boost a support skill puts on the character. Like a `StatusEffect` it counts down
+/// at the start of every round, recasting it refreshes the duration and keeps the stronger one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Buff {
+    pub kind: BuffKind,
+    pub amount: i32,
+    pub turns: u32,
+}
+
+impl Buff {
+    pub fn new(kind: BuffKind, amount: i32) -> Self {
+        Self {
+            kind,
+            amount: amount.max(1),
+            turns: BUFF_TURNS,
+        }
+    }
+
+    fn get(&self, kind: BuffKind) -> i32 {
+        match self.kind == kind {
+            true => self.amount,
+            false => 0,
+        }
+    }
+}
+
+impl Display for Buff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            BuffKind::Empower => write!(f, "{:?} +{}%", self.kind, self.amount)?,
+            _ => write!(f, "{:?} +{}", self.kind, self.amount)?,
+        }
+        write!(f, " ({} turns)", self.turns)
+    }
+}
+
+/// Every buff currently active on a character.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Buffs {
+    buffs: Vec<Buff>,
+}
+
+impl Buffs {
+    pub fn apply(&mut self, buff: Buff) {
+        match self.buffs.iter_mut().find(|b| b.kind == buff.kind) {
+            Some(active) => {
+                active.amount = active.amount.max(buff.amount);
+                active.turns = active.turns.max(buff.turns);
+            }
+            None => self.buffs.push(buff),
+        }
+    }
+
+    /// Counts every buff down by a turn and drops the ones that ran out.
+    pub fn tick(&mut self) {
+        self.buffs.iter_mut().for_each(|b| b.turns -= 1);
+        self.buffs.retain(|b| b.turns > 0);
+    }
+
+    pub fn is_active(&self, kind: BuffKind) -> bool {
+        self.buffs.iter().any(|b| b.kind == kind)
+    }
+
+    fn total(&self, kind: BuffKind) -> i32 {
+        self.buffs.iter().map(|b| b.get(kind)).sum()
+    }
+
+    pub fn armor(&self) -> i32 {
+        self.total(BuffKind::Armor)
+    }
+
+    pub fn ward(&self) -> i32 {
+        self.total(BuffKind::Ward)
+    }
+
+    pub fn dodge(&self) -> i32 {
+        self.total(BuffKind::Dodge)
+    }
+
+    pub fn action_points(&self) -> i32 {
+        self.total(BuffKind::Haste)
+    }
+
+    /// Added to the `multiplier` of the character's damage.
+    pub fn damage_multiplier(&self) -> f64 {
+        self.total(BuffKind::Empower) as f64 / 100.0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Buff> {
+        self.buffs.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buffs.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.buffs.clear();
+    }
+}
+
+impl Display for Buffs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let buffs: Vec<String> = self.buffs.iter().map(|b| b.to_string()).collect();
+        write!(f, "{}", buffs.join(", "))
+    }
+}
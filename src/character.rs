@@ -1,25 +1,63 @@
-use crate::enemy::Enemy;
-use crate::{level_up_scaling, AttributeScaling, BattleInfo};
+use crate::{level_up_scaling, AttributeScaling, BattleInfo, CarrionError, CarrionResult};
 
 use serde::{Deserialize, Serialize};
 
-use tracing::{info, trace};
+use tracing::info;
 
-use crate::class::Classes;
-use crate::damage::{DamageType, Defense};
+use crate::buff::Buffs;
+use crate::class::{Classes, MULTICLASS_LEVEL, PROMOTION_LEVEL, SECONDARY_CLASS_SHARE};
+use crate::dungeon::DungeonRun;
+use crate::encounter::Targeting;
+use crate::enemy::Enemy;
 use crate::r#trait::{CharacterTraits, TraitMutations};
-use crate::unit::Attributes;
+use crate::unit::{Attributes, ATTRIBUTES};
 
 use std::collections::HashSet;
 use std::hash::{Hash, Hasher};
 
 use std::fmt::Display;
 
-use crate::item::{Equipment, Items};
+use crate::item::{Equipment, Items, Loadouts};
 use strum::IntoEnumIterator;
 
+use crate::rotation::{Rotation, MAX_ROTATION_SKILLS};
 use crate::skill::{Skill, SkillSet};
-use tracing::log::debug;
+use crate::status::StatusEffects;
+use crate::zone::Zone;
+
+/// Attribute points the first traits spent on an attribute buy.
+pub const ATTRIBUTE_BOOST: i32 = 5;
+/// Traits spent on the same attribute before each one buys a point less, down to a single point.
+pub const ATTRIBUTE_BOOST_FALLOFF: i32 = 3;
+/// Gold per level a respec costs.
+pub const RESPEC_COST_PER_LEVEL: u64 = 100;
+/// Heartbeats a resting character needs to go from 0 to full HP.
+pub const REST_HEARTBEATS: u32 = 20;
+/// Heartbeats a dead character sits out before respawning with full HP.
+pub const RESPAWN_HEARTBEATS: u32 = 20;
+/// Share of the gold and experience lost on death.
+pub const DEATH_PENALTY: f64 = 0.1;
+
+/// What a discord character is doing between battles. HP is only restored by resting or by
+/// respawning, battles don't heal on their own.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Condition {
+    #[default]
+    Ready,
+    Resting,
+    /// Heartbeats left until the character respawns.
+    Dead(u32),
+}
+
+impl Display for Condition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Condition::Ready => write!(f, "Ready"),
+            Condition::Resting => write!(f, "Resting"),
+            Condition::Dead(heartbeats) => write!(f, "Dead, respawning in {heartbeats} turns"),
+        }
+    }
+}
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Eq)]
 pub struct Character {
@@ -36,6 +74,31 @@ pub struct Character {
     pub(crate) current_skill: SkillSet,
     pub(crate) equipment: Equipment,
     pub(crate) items: Items,
+    #[serde(default)]
+    pub(crate) condition: Condition,
+    #[serde(default)]
+    pub(crate) status: StatusEffects,
+    #[serde(default)]
+    pub(crate) loadouts: Loadouts,
+    #[serde(default)]
+    pub(crate) zone: Zone,
+    #[serde(default)]
+    pub(crate) dungeon: Option<DungeonRun>,
+    #[serde(default)]
+    pub(crate) secondary_class: Option<Classes>,
+    #[serde(default)]
+    pub(crate) buffs: Buffs,
+    #[serde(default)]
+    pub(crate) rotation: Rotation,
+    #[serde(default)]
+    pub(crate) targeting: Targeting,
+    /// Every trait spent on an attribute, with the points it bought, in the order spent.
+    #[serde(default)]
+    pub(crate) attribute_boosts: Vec<(String, i32)>,
+    /// Ascended skills unlocked so far. The skill table is the source of truth, the copy here
+    /// lets a replay header use them.
+    #[serde(default)]
+    pub(crate) ascended: Vec<Skill>,
 }
 impl Hash for Character {
     fn hash<H: Hasher>(&self, state: &mut H) {
@@ -47,6 +110,13 @@ impl Hash for Character {
         self.available_traits.hash(state);
         self.current_skill.hash(state);
         self.experience.hash(state);
+        self.condition.hash(state);
+        self.zone.hash(state);
+        self.dungeon.hash(state);
+        self.secondary_class.hash(state);
+        self.rotation.hash(state);
+        self.targeting.hash(state);
+        self.attribute_boosts.hash(state);
     }
 }
 
@@ -66,6 +136,17 @@ impl Default for Character {
             current_skill: SkillSet::default(),
             equipment: Default::default(),
             items: Default::default(),
+            condition: Condition::Ready,
+            status: StatusEffects::default(),
+            loadouts: Loadouts::default(),
+            zone: Zone::default(),
+            dungeon: None,
+            secondary_class: None,
+            buffs: Buffs::default(),
+            rotation: Rotation::default(),
+            targeting: Targeting::default(),
+            attribute_boosts: vec![],
+            ascended: vec![],
         }
     }
 }
@@ -75,28 +156,193 @@ impl Character {
         &self.traits
     }
 
-    // Only allow skills whose primary attribute matches the character's class
+    // Only allow skills whose primary attribute matches the character's class, the secondary
+    // class grants every skill of its stat. Ascended skills are always allowed once unlocked
     pub(crate) fn skill_list(&self) -> Vec<Skill> {
+        let secondary = self.secondary_class.map(|class| class.scaling());
         let mut skills = Vec::new();
         for skill in Skill::iter() {
+            if skill.is_ascended() {
+                if self.ascended.contains(&skill) {
+                    skills.push(skill);
+                }
+                continue;
+            }
             let attribute = AttributeScaling::scaling(&skill).unwrap();
             let attr = self.attributes.get(&attribute);
-            if attr > 17 {
+            if attr > 17 || secondary.as_ref() == Some(&attribute) {
                 skills.push(skill);
             }
         }
         skills
     }
+
+    /// The skill for the next action point, from the rotation if there is one.
+    pub(crate) fn next_skill(&mut self, enemies: &[Enemy]) -> SkillSet {
+        self.rotation
+            .next(self.hp, self.max_hp, enemies)
+            .unwrap_or_else(|| self.current_skill.clone())
+    }
+
+    /// Switches to a single skill, dropping the rotation.
+    pub(crate) fn use_skill(&mut self, skill: SkillSet) {
+        self.rotation = Rotation::default();
+        self.current_skill = skill;
+    }
+
+    /// Every skill the character is leveling, the current skill and the rotation.
+    pub(crate) fn skill_sets(&self) -> Vec<SkillSet> {
+        let mut skill_sets = vec![self.current_skill.clone()];
+        for skill_set in self.rotation.skill_sets() {
+            if skill_set.skill != self.current_skill.skill {
+                skill_sets.push(skill_set.clone());
+            }
+        }
+        skill_sets
+    }
+
+    /// Replaces the current skill with a rotation of skills from the `skill_list`. Skills found
+    /// in `known` or already used keep their experience, the first one becomes the current skill.
+    pub(crate) fn set_rotation(
+        &mut self,
+        mut rotation: Rotation,
+        known: &[SkillSet],
+    ) -> CarrionResult<()> {
+        let skills: Vec<Skill> = rotation.skills().collect();
+        if skills.is_empty() || skills.len() > MAX_ROTATION_SKILLS {
+            return Err(CarrionError::RotationError(format!(
+                "A rotation has 1 to {} skills",
+                MAX_ROTATION_SKILLS
+            )));
+        }
+        if let Some(skill) = skills
+            .iter()
+            .enumerate()
+            .find_map(|(i, skill)| skills[..i].contains(skill).then_some(skill))
+        {
+            return Err(CarrionError::RotationError(format!(
+                "{} is in the rotation twice",
+                skill
+            )));
+        }
+        let valid_skills = self.skill_list();
+        if let Some(skill) = skills.iter().find(|skill| !valid_skills.contains(skill)) {
+            return Err(CarrionError::RotationError(format!(
+                "{} is not one of your skills",
+                skill
+            )));
+        }
+
+        let used = self.skill_sets();
+        for skill_set in rotation.skill_sets_mut() {
+            if let Some(known) = known
+                .iter()
+                .chain(used.iter())
+                .find(|known| known.skill == skill_set.skill)
+            {
+                *skill_set = known.clone();
+            }
+        }
+        self.current_skill = rotation
+            .skill_sets()
+            .next()
+            .expect("Rotation has no skills")
+            .clone();
+        self.rotation = rotation;
+        Ok(())
+    }
+
+    /// Levels every skill by the experience it earned in the battle. Battles recorded before
+    /// rotations only have the total, which goes to the current skill.
+    pub(crate) fn gain_skill_experience(&mut self, battle_info: &BattleInfo) {
+        if battle_info.skill_experience.is_empty() {
+            self.current_skill.experience += battle_info.skill_experience_gained;
+            self.current_skill.try_level_up();
+            return;
+        }
+        for (skill, experience) in &battle_info.skill_experience {
+            for skill_set in std::iter::once(&mut self.current_skill)
+                .chain(self.rotation.skill_sets_mut())
+                .filter(|skill_set| skill_set.skill == *skill)
+            {
+                skill_set.experience += experience;
+                skill_set.try_level_up();
+            }
+        }
+    }
+
     pub(crate) fn insert_trait(&mut self, trait_: CharacterTraits) -> bool {
         info!("Inserting trait: {:?}", trait_);
+        if self.available_traits == 0 || !self.traits.insert(trait_) {
+            return false;
+        }
         trait_.attribute_mutator(&mut self.attributes);
         info!("Attributes after mutation {:?}", self.attributes);
-        if self.traits.insert(trait_) {
-            self.available_traits -= 1;
-            true
-        } else {
-            false
+        self.available_traits -= 1;
+        true
+    }
+
+    pub fn respec_cost(&self) -> u64 {
+        RESPEC_COST_PER_LEVEL * self.level as u64
+    }
+
+    /// Pays `respec_cost` out of `items` to take back every trait and attribute boost, with
+    /// the attributes they gave, and refund their points. Returns the points refunded.
+    pub fn respec(&mut self, items: &mut Items) -> CarrionResult<u32> {
+        let refund = (self.traits.len() + self.attribute_boosts.len()) as u32;
+        if refund == 0 {
+            return Err(CarrionError::TraitError(
+                "You have no traits to respec".to_string(),
+            ));
         }
+        let cost = self.respec_cost();
+        if items.gold < cost {
+            return Err(CarrionError::TraitError(format!(
+                "A respec costs {} gold, you have {}",
+                cost, items.gold
+            )));
+        }
+        items.gold -= cost;
+        for trait_ in self.traits.drain() {
+            trait_.attribute_reverter(&mut self.attributes);
+        }
+        for (attribute, boost) in self.attribute_boosts.drain(..) {
+            self.attributes.add(&attribute, -boost);
+        }
+        self.available_traits += refund;
+        Ok(refund)
+    }
+
+    /// Attribute points the next trait spent on `attribute` buys, fewer the more were spent on it.
+    pub fn attribute_boost(&self, attribute: &str) -> i32 {
+        let spent = self
+            .attribute_boosts
+            .iter()
+            .filter(|(boosted, _)| boosted == attribute)
+            .count() as i32;
+        (ATTRIBUTE_BOOST - spent / ATTRIBUTE_BOOST_FALLOFF).max(1)
+    }
+
+    /// Spends a trait point on a permanent boost to `attribute` instead of a trait, so spare
+    /// points are never wasted once every trait is taken. Returns the points bought.
+    pub fn boost_attribute(&mut self, attribute: &str) -> CarrionResult<i32> {
+        let attribute = attribute.to_lowercase();
+        if !ATTRIBUTES.contains(&attribute.as_str()) {
+            return Err(CarrionError::TraitError(format!(
+                "Invalid attribute, pick one of {}",
+                ATTRIBUTES.join(", ")
+            )));
+        }
+        if self.available_traits == 0 {
+            return Err(CarrionError::TraitError(
+                "No trait points available".to_string(),
+            ));
+        }
+        let boost = self.attribute_boost(&attribute);
+        self.attributes.add(&attribute, boost);
+        self.attribute_boosts.push((attribute, boost));
+        self.available_traits -= 1;
+        Ok(boost)
     }
 
     pub fn mutations(&self) -> TraitMutations {
@@ -110,14 +356,14 @@ impl Character {
         let mut base_action_points: i32 = 1;
         base_action_points += self.mutations().action_points();
         base_action_points += self.equipment.action_points();
+        base_action_points += self.buffs.action_points();
         base_action_points
     }
     pub fn new(name: String, user_id: u64, class: Classes) -> Self {
-        let max_hp = match class {
+        let max_hp = match class.base() {
             Classes::Warrior => 120,
-            Classes::Wizard => 80,
-            Classes::Sorcerer => 80,
             Classes::Paladin => 175,
+            _ => 80,
         };
 
         let base_skill = SkillSet::new(class.action());
@@ -136,23 +382,92 @@ impl Character {
             current_skill: base_skill,
             equipment: Default::default(),
             items: Default::default(),
+            condition: Condition::Ready,
+            status: StatusEffects::default(),
+            loadouts: Loadouts::default(),
+            zone: Zone::default(),
+            dungeon: None,
+            secondary_class: None,
+            buffs: Buffs::default(),
+            rotation: Rotation::default(),
+            targeting: Targeting::default(),
+            attribute_boosts: vec![],
+            ascended: vec![],
         }
     }
 
     pub fn hp_gain(&self, _level: u32) -> u32 {
         let constitution = self.attributes.constitution;
-        let hp_gain = match self.class {
-            Classes::Warrior => (constitution * 10) + 10,
-            Classes::Wizard => (constitution * 3) + 5,
-            Classes::Sorcerer => (constitution * 3) + 5,
-            Classes::Paladin => (constitution * 7) + 10,
-        } as u32;
-        hp_gain + self.max_hp
+        let mut hp_gain = self.class.hp_growth(constitution) as f64;
+        if let Some(secondary) = self.secondary_class {
+            hp_gain += secondary.hp_growth(constitution) as f64 * SECONDARY_CLASS_SHARE;
+        }
+        hp_gain as u32 + self.max_hp
+    }
+
+    pub fn armor_scaling(&self) -> f64 {
+        let secondary = self
+            .secondary_class
+            .map_or(0.0, |class| class.armor_scaling() * SECONDARY_CLASS_SHARE);
+        (self.class.armor_scaling() + secondary).min(1.0)
+    }
+
+    /// Moves the character into one of its class promotions once it reaches
+    /// `PROMOTION_LEVEL`, granting the attributes of the promoted class's stat.
+    pub fn promote(&mut self, class: Classes) -> CarrionResult<()> {
+        if !self.class.promotions().contains(&class) {
+            return Err(CarrionError::PromotionError(format!(
+                "A {} can not become a {}",
+                self.class, class
+            )));
+        }
+        if self.level < PROMOTION_LEVEL {
+            return Err(CarrionError::PromotionError(format!(
+                "Promotions unlock at level {}",
+                PROMOTION_LEVEL
+            )));
+        }
+        self.class = class;
+        self.attributes = self.attributes.clone() + Attributes::profile(&class);
+        Ok(())
+    }
+
+    /// Picks a secondary class, any base class other than the one the character started as.
+    /// It can be changed again later.
+    pub fn multiclass(&mut self, class: Classes) -> CarrionResult<()> {
+        if !class.is_base() || class == self.class.base() {
+            return Err(CarrionError::MulticlassError(format!(
+                "{} can not be your secondary class, pick one of {}",
+                class,
+                self.secondary_classes()
+                    .iter()
+                    .map(|class| class.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )));
+        }
+        if self.level < MULTICLASS_LEVEL {
+            return Err(CarrionError::MulticlassError(format!(
+                "Multi-classing unlocks at level {}",
+                MULTICLASS_LEVEL
+            )));
+        }
+        self.secondary_class = Some(class);
+        Ok(())
+    }
+
+    pub fn secondary_classes(&self) -> Vec<Classes> {
+        Classes::iter()
+            .filter(|class| class.is_base() && *class != self.class.base())
+            .collect()
     }
 
     pub fn try_trait_gain(&mut self) -> bool {
         if self.level % 10 == 0
-            || (self.available_traits + self.traits.len() as u32) < self.level / 10
+            || (self.available_traits
+                + self.traits.len() as u32
+                + self.attribute_boosts.len() as u32)
+                < self.level / 10
         {
             self.available_traits += 1;
             return true;
@@ -179,171 +494,88 @@ impl Character {
         self.hp = self.max_hp as i32;
     }
 
-    pub fn cli_player(&mut self, enemy: &mut Enemy) -> i32 {
-        let mut total_damage_done = 0;
-        for _ in 0..self.action_points() {
-            let mut damage = self.current_skill.act(self, enemy);
-            damage.apply_unique_cli(self, enemy);
-            if !enemy.alive() {
-                break;
-            }
-
-            let defense: Defense = enemy.into();
-
-            if defense.dodge() {
-                continue;
-            }
-
-            let mitigation = defense.defense(damage.dtype().resist_category());
-            let damage_taken_pre = damage.damage();
-            let damage_taken =
-                damage_taken_pre - (damage_taken_pre as f64 * mitigation / 100.0) as i32;
-
-            enemy.health -= damage_taken;
-            total_damage_done += damage_taken;
-
-            self.current_skill.experience += (enemy.experience / 10).max(1);
-        }
-
-        self.current_skill.try_level_up();
-
-        if !enemy.alive() {
-            for item in enemy.items.iter() {
-                if let Some(return_item) = self.equipment.auto_equip(item.clone()) {
-                    self.items.push(return_item);
-                }
-            }
+    pub fn can_battle(&self) -> bool {
+        self.condition == Condition::Ready && self.hp > 0
+    }
 
-            self.items.gold += enemy.gold;
-            self.experience += enemy.experience;
-            self.try_level_up();
-            self.try_trait_gain();
+    /// Starts resting, returns false if the character is dead or already at full HP.
+    pub fn start_rest(&mut self) -> bool {
+        if matches!(self.condition, Condition::Dead(_)) || self.hp >= self.max_hp as i32 {
+            return false;
         }
-        total_damage_done
+        self.condition = Condition::Resting;
+        true
     }
 
-    pub fn player_attack(&self, enemy: &Enemy, battle_info: &mut BattleInfo) {
-        for _ in 0..self.action_points() {
-            let mut damage = self.current_skill.act(self, enemy);
-            damage.appy_unique_effects(self, enemy, battle_info);
+    /// Loses a share of the experience towards the next level and waits to respawn.
+    pub fn die(&mut self) {
+        self.hp = 0;
+        self.experience -= (self.experience as f64 * DEATH_PENALTY) as u64;
+        self.condition = Condition::Dead(RESPAWN_HEARTBEATS);
+    }
 
-            if battle_info.enemy_killed {
-                break;
+    /// Advances resting or respawning by one heartbeat.
+    pub fn recover(&mut self) {
+        match self.condition {
+            Condition::Ready if self.hp <= 0 => {
+                self.condition = Condition::Resting;
             }
-
-            battle_info.number_of_player_attacks += 1;
-            let defense: Defense = enemy.into();
-
-            if defense.dodge() {
-                continue;
+            Condition::Ready => {}
+            Condition::Resting => {
+                let heal = (self.max_hp / REST_HEARTBEATS).max(1) as i32;
+                self.hp = (self.hp.max(0) + heal).min(self.max_hp as i32);
+                if self.hp == self.max_hp as i32 {
+                    self.condition = Condition::Ready;
+                }
             }
-
-            let mitigation = defense.defense(damage.dtype().resist_category());
-            let damage_taken_pre = damage.damage();
-            let damage_taken =
-                damage_taken_pre - (damage_taken_pre as f64 * mitigation / 100.0) as i32;
-
-            trace!(
-                "Mitigation: {:3} Damage Taken Pre: {} Damage Taken {} for damage type: {:?}",
-                mitigation,
-                damage_taken_pre,
-                damage_taken,
-                damage.dtype()
-            );
-
-            battle_info.player_damage += damage_taken;
-            battle_info.monster_hp = enemy.health;
-            debug!(
-                "{} attacked {} for {} damage! {} has {} hp",
-                self.name, enemy.kind, damage_taken, enemy.kind, enemy.health
-            );
-            battle_info.skill_experience_gained += (enemy.experience / 10).max(1);
-
-            if enemy.health <= (battle_info.player_damage - battle_info.enemy_healing) {
-                battle_info.enemy_killed = true;
+            Condition::Dead(0) | Condition::Dead(1) => {
+                self.rest();
+                self.condition = Condition::Ready;
+            }
+            Condition::Dead(heartbeats) => {
+                self.condition = Condition::Dead(heartbeats - 1);
             }
         }
+    }
+
+    /// Applies what a finished battle earned, the CLI counterpart of the discord mutations.
+    pub fn claim_rewards(&mut self, battle_info: &BattleInfo) {
+        self.gain_skill_experience(battle_info);
 
-        if battle_info.enemy_killed {
-            battle_info.item_gained.extend(enemy.items.clone());
-            battle_info.enemy_killed = true;
-            battle_info.gold_gained += enemy.gold;
-            battle_info.experience_gained = enemy.experience;
-            battle_info.traits_available = self.available_traits;
-            trace!(
-                "Experience Gained {} Next Level {} Curent Experience {}",
-                battle_info.experience_gained,
-                self.experience_to_next_level(),
-                self.experience
-            );
-            battle_info.next_level = self
-                .experience_to_next_level()
-                .saturating_sub(self.experience);
-        }
-    }
-
-    pub fn cli_enemy(&mut self, enemy: &mut Enemy) -> i32 {
-        let (damage, _action) = enemy.action();
-        let defense: Defense = self.into();
-        if damage.dtype() == DamageType::Healing {
-            let heal = damage.damage();
-            enemy.health += heal;
-            enemy.health = enemy.health.min(enemy.max_health() as i32);
-            return 0;
-        }
-
-        if defense.dodge() {
-            return 0;
-        }
-
-        let mitigation = defense.defense(damage.dtype().resist_category());
-        let damage_taken_pre = damage.damage();
-        let damage_taken = damage_taken_pre - (damage_taken_pre as f64 * mitigation / 100.0) as i32;
-        self.hp -= damage_taken;
-        self.hp = self.hp.max(0);
-        damage_taken
-    }
-    pub fn enemy_attack(&self, enemy: &Enemy, battle_info: &mut BattleInfo) {
-        let (damage, action) = enemy.action();
-        let defense: Defense = self.into();
-        if damage.dtype() == DamageType::Healing {
-            let heal = damage.damage();
-            battle_info.enemy_healing += heal;
-            battle_info.enemy_healing_action = action.to_string();
-            battle_info.monster_name = enemy.kind.to_string();
+        if !battle_info.enemy_killed {
             return;
         }
 
-        if defense.dodge() {
-            return;
+        for item in battle_info.item_gained.iter() {
+            if let Some(return_item) = self.equipment.auto_equip(item.clone()) {
+                self.items.push(return_item);
+            }
         }
 
-        let mitigation = defense.defense(damage.dtype().resist_category());
-        let damage_taken_pre = damage.damage();
-        let damage_taken = damage_taken_pre - (damage_taken_pre as f64 * mitigation / 100.0) as i32;
-        trace!(
-            "Mitigation: {} Damage Taken Pre: {} Damage Taken {} for damage type: {:?}",
-            mitigation,
-            damage_taken_pre,
-            damage_taken,
-            damage.dtype()
-        );
-        battle_info.enemy_damage += damage_taken;
-        if (battle_info.enemy_damage - battle_info.player_healing) > self.hp {
-            battle_info.player_killed = true;
+        self.items.gold += battle_info.gold_gained;
+        self.experience += battle_info.experience_gained;
+        self.try_level_up();
+        self.try_trait_gain();
+    }
+
+    fn class_name(&self) -> String {
+        match self.secondary_class {
+            Some(secondary) => format!("{} / {}", self.class, secondary),
+            None => self.class.to_string(),
         }
-        battle_info.enemy_action = action.to_string();
-        battle_info.monster_name = enemy.kind.to_string();
     }
 
     pub fn display_for_cli(&self) -> Vec<String> {
         let mut string = Vec::new();
         string.push(format!("Level: {}", self.level));
-        string.push(format!("Class: {}", self.class));
+        string.push(format!("Class: {}", self.class_name()));
         string.extend(self.attributes.display_for_cli());
         if !self.traits.is_empty() {
             string.push(format!("Traits: {:?}", self.traits));
         }
+        if !self.attribute_boosts.is_empty() {
+            string.push(format!("Attribute boosts: {}", self.attribute_boosts.len()));
+        }
         string
     }
 }
@@ -355,8 +587,13 @@ impl Display for Character {
         string.push('\n');
         string.push_str(&format!("Name: {}\n", self.name));
         string.push_str(&format!("Level: {}\n", self.level));
-        string.push_str(&format!("Class: {}\n", self.class));
+        string.push_str(&format!("Class: {}\n", self.class_name()));
         string.push_str(&format!("HP: {}/{}\n", self.hp, self.max_hp));
+        string.push_str(&format!("Condition: {}\n", self.condition));
+        string.push_str(&format!("Zone: {}\n", self.zone));
+        if let Some(dungeon) = &self.dungeon {
+            string.push_str(&format!("{}\n", dungeon));
+        }
         string.push_str(&format!("Experience: {}\n", self.experience));
         string.push_str(&format!("Attributes: {:?}\n", self.attributes));
         string.push_str("Traits:\n");
@@ -364,6 +601,12 @@ impl Display for Character {
         for tr in &self.traits {
             string.push_str(&format!("\t{}\n", tr));
         }
+        if !self.attribute_boosts.is_empty() {
+            string.push_str(&format!(
+                "Attribute boosts: {}\n",
+                self.attribute_boosts.len()
+            ));
+        }
         string.push_str(&format!("Equipment:\n{}", self.equipment));
         string.push_str("```");
         write!(f, "{}", string)
@@ -371,4 +614,102 @@ impl Display for Character {
 }
 
 #[cfg(test)]
-mod test {}
+mod test {
+    use crate::{
+        class::Classes,
+        enemy::{Enemy, Mob},
+        item::Items,
+        r#trait::CharacterTraits,
+        replay::BattleLog,
+        rng::CarrionRng,
+    };
+
+    use super::{
+        Character, Condition, ATTRIBUTE_BOOST, ATTRIBUTE_BOOST_FALLOFF, RESPAWN_HEARTBEATS,
+    };
+
+    #[test]
+    fn same_seed_same_battle() {
+        let character = Character::new("Seeded".into(), 1, Classes::Paladin);
+        let enemies: [Enemy; 1] = [Mob::Orc.generate(character.level, &mut CarrionRng::new(42))];
+        assert_eq!(
+            BattleLog::synchro(&character, &enemies, 42),
+            BattleLog::synchro(&character, &enemies, 42)
+        );
+    }
+
+    #[test]
+    fn death_waits_to_respawn_and_rest_heals_over_time() {
+        let mut character = Character::new("Mortal".into(), 1, Classes::Warrior);
+        character.experience = 100;
+        character.die();
+        assert_eq!(character.experience, 90);
+        assert!(!character.can_battle());
+        assert!(!character.start_rest());
+        for _ in 0..RESPAWN_HEARTBEATS {
+            character.recover();
+        }
+        assert_eq!(character.condition, Condition::Ready);
+        assert_eq!(character.hp, character.max_hp as i32);
+
+        character.hp = 1;
+        assert!(character.start_rest());
+        let mut heartbeats = 0;
+        while !character.can_battle() {
+            character.recover();
+            heartbeats += 1;
+        }
+        assert_eq!(character.hp, character.max_hp as i32);
+        assert!(heartbeats > 1);
+    }
+
+    #[test]
+    fn spare_traits_boost_attributes_with_diminishing_returns() {
+        let mut character = Character::new("Spender".into(), 1, Classes::Warrior);
+        assert!(character.boost_attribute("strength").is_err());
+        let strength = character.attributes.strength;
+        character.available_traits = 100;
+        assert!(character.boost_attribute("luck").is_err());
+
+        let boosts: Vec<i32> = (0..100)
+            .map(|_| character.boost_attribute("Strength").unwrap())
+            .collect();
+        assert_eq!(
+            boosts[..ATTRIBUTE_BOOST_FALLOFF as usize],
+            [ATTRIBUTE_BOOST; 3]
+        );
+        assert!(boosts.windows(2).all(|pair| pair[0] >= pair[1]));
+        assert_eq!(boosts.last(), Some(&1));
+        assert_eq!(
+            character.attributes.strength,
+            strength + boosts.iter().sum::<i32>()
+        );
+        assert_eq!(character.available_traits, 0);
+        assert_eq!(character.attribute_boost("dexterity"), ATTRIBUTE_BOOST);
+    }
+
+    #[test]
+    fn respec_refunds_traits_and_boosts() {
+        let mut character = Character::new("Fickle".into(), 30, Classes::Wizard);
+        let mut items = Items::default();
+        let attributes = character.attributes.clone();
+        assert!(character.respec(&mut items).is_err());
+
+        character.available_traits = 4;
+        assert!(character.insert_trait(CharacterTraits::Addict));
+        assert!(!character.insert_trait(CharacterTraits::Addict));
+        assert!(character.insert_trait(CharacterTraits::FolkHero));
+        character.boost_attribute("wisdom").unwrap();
+        assert_ne!(character.attributes, attributes);
+        assert_eq!(character.available_traits, 1);
+
+        items.gold = character.respec_cost() - 1;
+        assert!(character.respec(&mut items).is_err());
+        items.gold += 1;
+        assert_eq!(character.respec(&mut items).unwrap(), 3);
+        assert_eq!(items.gold, 0);
+        assert_eq!(character.attributes, attributes);
+        assert_eq!(character.available_traits, 4);
+        assert!(character.get_traits().is_empty());
+    }
+}
@@ -3,7 +3,11 @@ use crate::{skill::Skill, AttributeScaling};
 use eris_macro::{AttributeScaling, ErisDisplayEmoji, ErisValidEnum};
 
 use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
 
+/// Characters are created as one of the four base classes and promoted into one of two
+/// classes at `PROMOTION_LEVEL`. Both databases store the class by name, so the base classes
+/// must keep their names for existing characters to load.
 #[derive(
     Debug,
     Clone,
@@ -16,6 +20,7 @@ use serde::{Deserialize, Serialize};
     AttributeScaling,
     Hash,
     Eq,
+    strum::EnumIter,
 )]
 pub enum Classes {
     #[stat("strength")]
@@ -30,8 +35,39 @@ pub enum Classes {
     #[stat("charisma")]
     #[emoji("🛡️")]
     Paladin,
+    #[stat("strength")]
+    #[emoji("🪓")]
+    Berserker,
+    #[stat("constitution")]
+    #[emoji("🏰")]
+    Knight,
+    #[stat("intelligence")]
+    #[emoji("🌌")]
+    Archmage,
+    #[stat("dexterity")]
+    #[emoji("🗡️")]
+    Spellblade,
+    #[stat("intelligence")]
+    #[emoji("🕯️")]
+    Warlock,
+    #[stat("wisdom")]
+    #[emoji("👁️")]
+    Oracle,
+    #[stat("wisdom")]
+    #[emoji("⛪")]
+    Templar,
+    #[stat("charisma")]
+    #[emoji("✝️")]
+    Crusader,
 }
 
+/// Level a character has to reach before it can pick one of its class promotions.
+pub const PROMOTION_LEVEL: u32 = 25;
+/// Level a character has to reach before it can pick a secondary class.
+pub const MULTICLASS_LEVEL: u32 = 10;
+/// Share of the secondary class's HP growth and armor scaling a character gets.
+pub const SECONDARY_CLASS_SHARE: f64 = 0.25;
+
 impl Classes {
     pub fn armor_scaling(&self) -> f64 {
         match self {
@@ -39,6 +75,14 @@ impl Classes {
             Classes::Wizard => 0.1,
             Classes::Sorcerer => 0.1,
             Classes::Paladin => 0.6,
+            Classes::Berserker => 0.6,
+            Classes::Knight => 1.0,
+            Classes::Archmage => 0.1,
+            Classes::Spellblade => 0.3,
+            Classes::Warlock => 0.1,
+            Classes::Oracle => 0.2,
+            Classes::Templar => 0.7,
+            Classes::Crusader => 0.8,
         }
     }
     pub fn action(&self) -> Skill {
@@ -47,9 +91,117 @@ impl Classes {
             Classes::Wizard => Skill::MagicMissile,
             Classes::Sorcerer => Skill::FireBall,
             Classes::Paladin => Skill::Rapture,
+            Classes::Berserker => Skill::SteelRain,
+            Classes::Knight => Skill::EarthShatter,
+            Classes::Archmage => Skill::RadiantIntellect,
+            Classes::Spellblade => Skill::ArcaneNeedle,
+            Classes::Warlock => Skill::AbsorbLife,
+            Classes::Oracle => Skill::DivineBlessing,
+            Classes::Templar => Skill::HolySmite,
+            Classes::Crusader => Skill::Excoriate,
+        }
+    }
+    /// HP gained per level for the given constitution.
+    pub fn hp_growth(&self, constitution: i32) -> i32 {
+        match self {
+            Classes::Warrior => (constitution * 10) + 10,
+            Classes::Wizard => (constitution * 3) + 5,
+            Classes::Sorcerer => (constitution * 3) + 5,
+            Classes::Paladin => (constitution * 7) + 10,
+            Classes::Berserker => (constitution * 12) + 10,
+            Classes::Knight => (constitution * 11) + 15,
+            Classes::Archmage => (constitution * 3) + 5,
+            Classes::Spellblade => (constitution * 5) + 5,
+            Classes::Warlock => (constitution * 3) + 5,
+            Classes::Oracle => (constitution * 4) + 5,
+            Classes::Templar => (constitution * 8) + 10,
+            Classes::Crusader => (constitution * 8) + 10,
         }
     }
     pub fn scaling(&self) -> String {
         AttributeScaling::scaling(self).unwrap()
     }
+
+    /// The class a character is created as, promoted classes keep track of where they came from.
+    pub fn base(&self) -> Classes {
+        match self {
+            Classes::Berserker | Classes::Knight => Classes::Warrior,
+            Classes::Archmage | Classes::Spellblade => Classes::Wizard,
+            Classes::Warlock | Classes::Oracle => Classes::Sorcerer,
+            Classes::Templar | Classes::Crusader => Classes::Paladin,
+            base => *base,
+        }
+    }
+
+    pub fn is_base(&self) -> bool {
+        self.base() == *self
+    }
+
+    /// What the class can be promoted to, empty once it has been promoted.
+    pub fn promotions(&self) -> &'static [Classes] {
+        match self {
+            Classes::Warrior => &[Classes::Berserker, Classes::Knight],
+            Classes::Wizard => &[Classes::Archmage, Classes::Spellblade],
+            Classes::Sorcerer => &[Classes::Warlock, Classes::Oracle],
+            Classes::Paladin => &[Classes::Templar, Classes::Crusader],
+            _ => &[],
+        }
+    }
+
+    /// Classes a new character can pick from.
+    pub fn starting() -> Vec<String> {
+        Classes::iter()
+            .filter(Classes::is_base)
+            .map(|class| class.to_string())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Classes, MULTICLASS_LEVEL, PROMOTION_LEVEL};
+    use crate::{character::Character, skill::Skill};
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn promotions_come_from_their_base_class() {
+        for class in Classes::iter().filter(Classes::is_base) {
+            assert_eq!(class.promotions().len(), 2);
+            for promotion in class.promotions() {
+                assert_eq!(promotion.base(), class);
+                assert!(promotion.promotions().is_empty());
+            }
+        }
+        assert_eq!(Classes::starting().len(), 4);
+    }
+
+    #[test]
+    fn promotions_unlock_at_the_promotion_level() {
+        let mut character = Character::new("Promoted".into(), 1, Classes::Warrior);
+        assert!(character.promote(Classes::Berserker).is_err());
+        character.level = PROMOTION_LEVEL;
+        assert!(character.promote(Classes::Archmage).is_err());
+
+        let strength = character.attributes.strength;
+        character.promote(Classes::Berserker).unwrap();
+        assert_eq!(character.class, Classes::Berserker);
+        assert!(character.attributes.strength > strength);
+        assert!(character.promote(Classes::Knight).is_err());
+    }
+
+    #[test]
+    fn secondary_classes_grant_their_skills() {
+        let mut character = Character::new("Dabbler".into(), 1, Classes::Warrior);
+        assert!(character.multiclass(Classes::Wizard).is_err());
+        character.level = MULTICLASS_LEVEL;
+        assert!(character.multiclass(Classes::Warrior).is_err());
+        assert!(character.multiclass(Classes::Archmage).is_err());
+
+        let hp_gain = character.hp_gain(character.level);
+        assert!(!character.skill_list().contains(&Skill::MagicMissile));
+        character.multiclass(Classes::Wizard).unwrap();
+        assert!(character.skill_list().contains(&Skill::MagicMissile));
+        assert!(character.hp_gain(character.level) > hp_gain);
+        assert!(character.armor_scaling() > Classes::Warrior.armor_scaling());
+    }
 }
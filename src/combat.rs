@@ -0,0 +1,660 @@
+use rand::Rng;
+use tracing::{debug, trace};
+
+use crate::buff::Buff;
+use crate::character::Character;
+use crate::damage::{DamageType, Defense};
+use crate::enemy::Enemy;
+use crate::replay::{BattleEvent, Combatant};
+use crate::skill::{SkillSet, Support};
+use crate::status::{StatusEffect, StatusTick};
+use crate::BattleInfo;
+
+/// The single combat engine shared by every frontend. It mutates the `Character` and the
+/// `Enemy` group it is given, accumulates totals and rewards into `BattleInfo` and reports every
+/// roll as a `BattleEvent`. Discord runs it on copies until the battle is over, the CLI runs one
+/// round per `fight` command on its live state. The battle is won once every enemy is dead.
+pub fn round(
+    character: &mut Character,
+    enemies: &mut [Enemy],
+    battle_info: &mut BattleInfo,
+    rng: &mut impl Rng,
+) -> Vec<BattleEvent> {
+    let mut events = vec![];
+    battle_info.rounds += 1;
+    character.buffs.tick();
+    tick_statuses(character, enemies, battle_info, rng, &mut events);
+    if finished(battle_info) {
+        return events;
+    }
+    regenerate(character, battle_info, &mut events);
+
+    player_turn(character, enemies, battle_info, rng, &mut events);
+
+    for index in 0..enemies.len() {
+        if finished(battle_info) {
+            break;
+        }
+        if enemies[index].alive() {
+            enemy_turn(
+                character,
+                &mut enemies[index],
+                index,
+                battle_info,
+                rng,
+                &mut events,
+            );
+            // Thorns can kill the enemy on its own turn
+            if !enemies[index].alive() {
+                enemy_killed(character, enemies, index, battle_info, rng, &mut events);
+            }
+        }
+    }
+    events
+}
+
+pub fn finished(battle_info: &BattleInfo) -> bool {
+    battle_info.enemy_killed || battle_info.player_killed
+}
+
+/// Applies the defender's mitigation, then the extra damage its status effects let through.
+fn mitigate(damage: i32, mitigation: f64, vulnerability: f64) -> i32 {
+    let damage = damage - (damage as f64 * mitigation / 100.0) as i32;
+    damage.saturating_add((damage as f64 * vulnerability) as i32)
+}
+
+/// Ticks the status effects of every combatant at the start of a round, the enemies' first.
+fn tick_statuses(
+    character: &mut Character,
+    enemies: &mut [Enemy],
+    battle_info: &mut BattleInfo,
+    rng: &mut impl Rng,
+    events: &mut Vec<BattleEvent>,
+) {
+    for index in 0..enemies.len() {
+        let enemy = &mut enemies[index];
+        if !enemy.alive() {
+            continue;
+        }
+        for status in enemy.status.tick() {
+            let amount = if status.heals() {
+                let healed = status
+                    .amount()
+                    .min(enemy.max_health() as i32 - enemy.health)
+                    .max(0);
+                enemy.health += healed;
+                battle_info.enemy_healing += healed;
+                battle_info.update_enemy(index, enemy, 0, healed);
+                healed
+            } else {
+                enemy.health -= status.amount();
+                battle_info.player_damage += status.amount();
+                battle_info.update_enemy(index, enemy, status.amount(), 0);
+                status.amount()
+            };
+            record_tick(Combatant::Enemy, status, amount, battle_info, events);
+        }
+        if !enemy.alive() {
+            enemy_killed(character, enemies, index, battle_info, rng, events);
+        }
+    }
+    if battle_info.enemy_killed {
+        return;
+    }
+
+    let mut damaged = false;
+    for status in character.status.tick() {
+        let amount = if status.heals() {
+            heal_player(character, status.amount(), battle_info)
+        } else {
+            damaged = true;
+            let dealt = status.amount().min(character.hp);
+            character.hp -= dealt;
+            battle_info.enemy_damage += dealt;
+            dealt
+        };
+        record_tick(Combatant::Player, status, amount, battle_info, events);
+    }
+    if damaged && character.hp == 0 {
+        battle_info.player_killed = true;
+        events.push(BattleEvent::Killed(Combatant::Player));
+    }
+}
+
+/// Heals the character by the regeneration of its traits at the start of its turn.
+fn regenerate(
+    character: &mut Character,
+    battle_info: &mut BattleInfo,
+    events: &mut Vec<BattleEvent>,
+) {
+    let amount = character.mutations().regeneration(character.max_hp);
+    let healed = heal_player(character, amount, battle_info);
+    if healed > 0 {
+        events.push(BattleEvent::heal(
+            Combatant::Player,
+            "Regeneration".to_string(),
+            healed,
+        ));
+    }
+}
+
+fn record_tick(
+    target: Combatant,
+    status: StatusEffect,
+    amount: i32,
+    battle_info: &mut BattleInfo,
+    events: &mut Vec<BattleEvent>,
+) {
+    let tick = StatusTick {
+        target,
+        status,
+        amount,
+    };
+    trace!("Round {}: {}", battle_info.rounds, tick);
+    battle_info.status_ticks.push((battle_info.rounds, tick));
+    events.push(BattleEvent::Status(tick));
+}
+
+/// Spends every action point on the next skill. Single target skills hit the enemy picked by
+/// the character's `Targeting`, area skills hit every enemy still standing.
+pub fn player_turn(
+    character: &mut Character,
+    enemies: &mut [Enemy],
+    battle_info: &mut BattleInfo,
+    rng: &mut impl Rng,
+    events: &mut Vec<BattleEvent>,
+) {
+    for _ in 0..character.action_points() {
+        let skill_set = character.next_skill(enemies);
+        if support_cast(character, &skill_set, battle_info, events) {
+            continue;
+        }
+
+        // Support skills fall back to the class skill while they have nothing to do
+        let attack = match skill_set.skill().is_support() {
+            true => {
+                let class_skill = character.class.action();
+                character
+                    .skill_sets()
+                    .into_iter()
+                    .find(|known| known.skill() == class_skill)
+                    .unwrap_or_else(|| SkillSet::new(class_skill))
+            }
+            false => skill_set,
+        };
+        let targets: Vec<usize> = match attack.skill().is_area() {
+            true => (0..enemies.len())
+                .filter(|index| enemies[*index].alive())
+                .collect(),
+            false => character
+                .targeting
+                .pick(attack.skill(), enemies)
+                .into_iter()
+                .collect(),
+        };
+        for index in targets {
+            if let Some(experience) =
+                strike(character, &attack, enemies, index, battle_info, rng, events)
+            {
+                battle_info.gain_skill_experience(attack.skill(), experience);
+            }
+        }
+
+        if battle_info.enemy_killed {
+            break;
+        }
+    }
+}
+
+/// Lands a single attack on the enemy at `index`, returns the skill experience of a hit.
+fn strike(
+    character: &mut Character,
+    attack: &SkillSet,
+    enemies: &mut [Enemy],
+    index: usize,
+    battle_info: &mut BattleInfo,
+    rng: &mut impl Rng,
+    events: &mut Vec<BattleEvent>,
+) -> Option<u64> {
+    let mut experience = None;
+    let enemy = &mut enemies[index];
+    let mut damage = attack.act(character, enemy, rng);
+    BattleEvent::effects(&damage, events);
+    damage.apply_unique_effects(character, enemy, battle_info, rng);
+
+    if enemy.alive() {
+        battle_info.number_of_player_attacks += 1;
+        let defense: Defense = (&*enemy).into();
+        let action = attack.skill().to_string();
+
+        if defense.dodge(rng) {
+            events.push(BattleEvent::dodge(Combatant::Player, action));
+            return None;
+        }
+
+        let mitigation = defense.defense(damage.dtype().resist_category());
+        let super_effective = defense.super_effective(damage.dtype().resist_category());
+        let (damage_taken_pre, crits) = damage.roll(rng);
+        let damage_taken = mitigate(
+            damage_taken_pre,
+            mitigation,
+            enemy.status.vulnerability() + defense.weakness(&damage),
+        );
+        battle_info.critical |= crits > 0;
+        battle_info.super_effective |= super_effective;
+        events.push(BattleEvent::hit(
+            Combatant::Player,
+            action,
+            &damage,
+            damage_taken_pre,
+            damage_taken,
+            crits,
+            super_effective,
+        ));
+
+        trace!(
+            "Mitigation: {:3} Damage Taken Pre: {} Damage Taken {} for damage type: {:?}",
+            mitigation,
+            damage_taken_pre,
+            damage_taken,
+            damage.dtype()
+        );
+
+        enemy.health -= damage_taken;
+        battle_info.player_damage += damage_taken;
+        battle_info.update_enemy(index, enemy, damage_taken, 0);
+        debug!(
+            "{} attacked {} for {} damage! {} has {} hp",
+            character.name, enemy.kind, damage_taken, enemy.kind, enemy.health
+        );
+        let stolen = character.mutations().on_hit(damage_taken);
+        let healed = heal_player(character, stolen, battle_info);
+        if healed > 0 {
+            events.push(BattleEvent::heal(
+                Combatant::Player,
+                "Lifesteal".to_string(),
+                healed,
+            ));
+        }
+        experience = Some((enemy.experience / 10).max(1));
+    }
+
+    if !enemies[index].alive() {
+        enemy_killed(character, enemies, index, battle_info, rng, events);
+    }
+    experience
+}
+
+/// Casts the skill if it is a support skill with something to do, a heal below half HP or a
+/// buff that is not up. Casting takes the action point.
+fn support_cast(
+    character: &mut Character,
+    skill_set: &SkillSet,
+    battle_info: &mut BattleInfo,
+    events: &mut Vec<BattleEvent>,
+) -> bool {
+    let skill = skill_set.skill();
+    let amount = skill_set.support_amount(character);
+    match skill.support() {
+        Some(Support::Heal) if character.hp * 2 < character.max_hp as i32 => {
+            let healed = heal_player(character, amount, battle_info);
+            events.push(BattleEvent::heal(
+                Combatant::Player,
+                skill.to_string(),
+                healed,
+            ));
+            true
+        }
+        Some(Support::Buff(kind)) if !character.buffs.is_active(kind) => {
+            let buff = Buff::new(kind, amount);
+            character.buffs.apply(buff);
+            battle_info.buffs.push((battle_info.rounds, buff));
+            events.push(BattleEvent::Buff {
+                action: skill.to_string(),
+                buff,
+            });
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Pays out the enemy at `index`, the battle is won once the whole group is dead. The
+/// character's traits change the gold and can find an extra drop or lose it.
+fn enemy_killed(
+    character: &Character,
+    enemies: &[Enemy],
+    index: usize,
+    battle_info: &mut BattleInfo,
+    rng: &mut impl Rng,
+    events: &mut Vec<BattleEvent>,
+) {
+    let enemy = &enemies[index];
+    let mutations = character.mutations();
+    events.push(BattleEvent::Killed(Combatant::Enemy));
+    battle_info.enemy_killed = enemies.iter().all(|enemy| !enemy.alive());
+    battle_info.update_enemy(index, enemy, 0, 0);
+    battle_info.monster_hp = enemy.health.max(0);
+    let item_find = mutations.item_find();
+    let found = item_find != 0.0 && rng.gen_bool(item_find.abs());
+    if !(found && item_find < 0.0) {
+        battle_info.item_gained.extend(enemy.items.clone());
+    }
+    if found && item_find > 0.0 {
+        battle_info
+            .item_gained
+            .extend(crate::items::drops(enemy, rng));
+    }
+    battle_info.gold_gained += mutations.gold(enemy.gold);
+    battle_info.experience_gained += enemy.experience;
+    battle_info.traits_available = character.available_traits;
+    trace!(
+        "Experience Gained {} Next Level {} Curent Experience {}",
+        battle_info.experience_gained,
+        character.experience_to_next_level(),
+        character.experience
+    );
+    battle_info.next_level = character
+        .experience_to_next_level()
+        .saturating_sub(character.experience);
+}
+
+pub fn enemy_turn(
+    character: &mut Character,
+    enemy: &mut Enemy,
+    index: usize,
+    battle_info: &mut BattleInfo,
+    rng: &mut impl Rng,
+    events: &mut Vec<BattleEvent>,
+) {
+    let (damage, action) = enemy.action(rng);
+    battle_info.number_of_enemy_attacks += 1;
+    if let Some(report) = battle_info.enemies.get_mut(index) {
+        report.opened = enemy.opened;
+    }
+
+    if damage.dtype() == DamageType::Healing {
+        let heal = damage.damage(rng);
+        let healed = heal.min(enemy.max_health() as i32 - enemy.health).max(0);
+        enemy.health += healed;
+        battle_info.enemy_healing += healed;
+        battle_info.enemy_healing_action = action.to_string();
+        battle_info.update_enemy(index, enemy, 0, healed);
+        events.push(BattleEvent::heal(
+            Combatant::Enemy,
+            action.to_string(),
+            healed,
+        ));
+        return;
+    }
+
+    let defense: Defense = (&*character).into();
+    if defense.dodge(rng) {
+        events.push(BattleEvent::dodge(Combatant::Enemy, action.to_string()));
+        return;
+    }
+
+    let mitigation = defense.defense(damage.dtype().resist_category());
+    let (damage_taken_pre, crits) = damage.roll(rng);
+    let damage_taken = mitigate(
+        damage_taken_pre,
+        mitigation,
+        character.status.vulnerability() + defense.weakness(&damage),
+    );
+    events.push(BattleEvent::hit(
+        Combatant::Enemy,
+        action.to_string(),
+        &damage,
+        damage_taken_pre,
+        damage_taken,
+        crits,
+        defense.super_effective(damage.dtype().resist_category()),
+    ));
+    trace!(
+        "Mitigation: {} Damage Taken Pre: {} Damage Taken {} for damage type: {:?}",
+        mitigation,
+        damage_taken_pre,
+        damage_taken,
+        damage.dtype()
+    );
+
+    character.hp = (character.hp - damage_taken).max(0);
+    battle_info.enemy_damage += damage_taken;
+    battle_info.enemy_action = action.to_string();
+    if character.hp == 0 {
+        battle_info.player_killed = true;
+        events.push(BattleEvent::Killed(Combatant::Player));
+        return;
+    }
+    for effect in &damage.unique_effect {
+        effect.afflict(character, &damage);
+        events.push(BattleEvent::Effect {
+            attacker: Combatant::Enemy,
+            effect: *effect,
+        });
+    }
+
+    let reflected = character
+        .mutations()
+        .on_damage_taken(damage_taken)
+        .min(enemy.health);
+    if reflected > 0 {
+        enemy.health -= reflected;
+        battle_info.player_damage += reflected;
+        battle_info.update_enemy(index, enemy, reflected, 0);
+        events.push(BattleEvent::Reflect {
+            attacker: Combatant::Player,
+            action: "Thorns".to_string(),
+            amount: reflected,
+        });
+    }
+}
+
+/// Heals the character without going over `max_hp` and records what was actually healed.
+pub(crate) fn heal_player(
+    character: &mut Character,
+    amount: i32,
+    battle_info: &mut BattleInfo,
+) -> i32 {
+    let healed = amount.min(character.max_hp as i32 - character.hp).max(0);
+    character.hp += healed;
+    battle_info.player_healing += healed;
+    healed
+}
+
+#[cfg(test)]
+mod test {
+    use super::{finished, round};
+    use crate::{
+        buff::{BuffKind, BUFF_TURNS},
+        character::Character,
+        class::Classes,
+        damage::UniqueDamageEffect,
+        encounter::Targeting,
+        enemy::Mob,
+        r#trait::CharacterTraits,
+        replay::{BattleEvent, Combatant},
+        rng::CarrionRng,
+        skill::{Skill, SkillSet},
+        status::StatusEffect,
+        BattleInfo,
+    };
+
+    #[test]
+    fn enemy_healing_is_capped_at_max_health() {
+        let mut rng = CarrionRng::new(3);
+        for mob in [Mob::Orc, Mob::Eldragor] {
+            let mut character = Character::new("Capped".into(), 1, Classes::Warrior);
+            let mut enemies = [mob.generate(character.level, &mut rng)];
+            let mut battle_info = BattleInfo::begin(&character, &enemies);
+            while !finished(&battle_info) {
+                round(&mut character, &mut enemies, &mut battle_info, &mut rng);
+                assert!(enemies[0].health <= enemies[0].max_health() as i32);
+                assert!(character.hp >= 0);
+            }
+            assert_eq!(battle_info.player_killed, character.hp == 0);
+            assert_eq!(battle_info.enemy_killed, !enemies[0].alive());
+        }
+    }
+
+    #[test]
+    fn status_effects_tick_at_the_start_of_each_round() {
+        let mut rng = CarrionRng::new(5);
+        let mut character = Character::new("Toxic".into(), 1, Classes::Wizard);
+        let mut enemies = [Mob::Orc.generate(character.level, &mut rng)];
+        let poison = StatusEffect::new(UniqueDamageEffect::Poison, 2).unwrap();
+        enemies[0].status.apply(poison.with_stacks(2));
+        let mut battle_info = BattleInfo::begin(&character, &enemies);
+
+        let events = round(&mut character, &mut enemies, &mut battle_info, &mut rng);
+        assert!(matches!(
+            events.first(),
+            Some(BattleEvent::Status(tick)) if tick.target == Combatant::Enemy && tick.amount == 4
+        ));
+        assert_eq!(battle_info.status_ticks.len(), 1);
+        assert_eq!(battle_info.status_ticks[0].0, 1);
+        assert_eq!(
+            enemies[0].status.iter().next().unwrap().turns,
+            poison.turns - 1
+        );
+    }
+
+    #[test]
+    fn enemy_afflictions_tick_on_the_player() {
+        let mut rng = CarrionRng::new(23);
+        let mut character = Character::new("Bitten".into(), 1, Classes::Warrior);
+        character.max_hp = 10_000;
+        character.hp = 10_000;
+        let mut enemies = [Mob::Goblin.generate(character.level, &mut rng)];
+        enemies[0].health = 100_000;
+        let mut battle_info = BattleInfo::begin(&character, &enemies);
+        while character.status.is_empty() {
+            assert!(battle_info.rounds < 50, "The goblin never bit");
+            round(&mut character, &mut enemies, &mut battle_info, &mut rng);
+        }
+
+        let enemy_damage = battle_info.enemy_damage;
+        let events = round(&mut character, &mut enemies, &mut battle_info, &mut rng);
+        let ticked = events.iter().find_map(|event| match event {
+            BattleEvent::Status(tick) if tick.target == Combatant::Player => Some(tick.amount),
+            _ => None,
+        });
+        assert_eq!(ticked, Some(10_000 / 50));
+        assert!(battle_info
+            .status_ticks
+            .iter()
+            .any(|(_, tick)| tick.target == Combatant::Player));
+        assert!(battle_info.enemy_damage >= enemy_damage + 10_000 / 50);
+    }
+
+    #[test]
+    fn support_skills_buff_and_heal_instead_of_attacking() {
+        let mut rng = CarrionRng::new(8);
+        let mut character = Character::new("Support".into(), 1, Classes::Paladin);
+        character.current_skill = SkillSet::new(Skill::DivineAegis);
+        let mut enemies = [Mob::Orc.generate(character.level, &mut rng)];
+        let armor = crate::damage::Defense::new(&character).physical_mitigation();
+        let mut battle_info = BattleInfo::begin(&character, &enemies);
+
+        let events = round(&mut character, &mut enemies, &mut battle_info, &mut rng);
+        assert!(matches!(events.first(), Some(BattleEvent::Buff { .. })));
+        assert!(character.buffs.is_active(BuffKind::Armor));
+        assert!(crate::damage::Defense::new(&character).physical_mitigation() > armor);
+        assert_eq!(battle_info.player_damage, 0);
+        assert_eq!(battle_info.buffs.len(), 1);
+
+        // The buff is up, so the class skill attacks until it runs out
+        for _ in 1..BUFF_TURNS {
+            round(&mut character, &mut enemies, &mut battle_info, &mut rng);
+        }
+        assert_eq!(battle_info.buffs.len(), 1);
+        assert!(battle_info.number_of_player_attacks > 0);
+        // Only the class skill that did the hitting levels from it
+        assert!(battle_info
+            .skill_experience
+            .iter()
+            .all(|(skill, _)| *skill == character.class.action()));
+
+        character.current_skill = SkillSet::new(Skill::LayOnHands);
+        // The orc's bites would tick the character down before the heal
+        character.status = Default::default();
+        character.hp = 1;
+        let events = round(&mut character, &mut enemies, &mut battle_info, &mut rng);
+        assert!(matches!(
+            events.iter().find(|e| matches!(e, BattleEvent::Heal { .. })),
+            Some(BattleEvent::Heal { target: Combatant::Player, amount, .. }) if *amount > 0
+        ));
+    }
+
+    #[test]
+    fn area_skills_hit_every_enemy_of_an_encounter() {
+        let mut rng = CarrionRng::new(19);
+        let mut character = Character::new("Quake".into(), 1, Classes::Warrior);
+        let mut enemies = [
+            Mob::Orc.generate(character.level, &mut rng),
+            Mob::Goblin.generate(character.level, &mut rng),
+            Mob::Elf.generate(character.level, &mut rng),
+        ];
+        enemies.iter_mut().for_each(|enemy| enemy.health = 10_000);
+        enemies[2].health = 5_000;
+        character.targeting = Targeting::Weakest;
+        let mut battle_info = BattleInfo::begin(&character, &enemies);
+        assert_eq!(battle_info.enemies.len(), 3);
+
+        round(&mut character, &mut enemies, &mut battle_info, &mut rng);
+        assert!(battle_info.enemies[2].damage_taken > 0);
+        assert!(battle_info.enemies[..2].iter().all(|e| e.damage_taken == 0));
+        assert_eq!(battle_info.number_of_enemy_attacks, 3);
+
+        character.current_skill = SkillSet::new(Skill::SteelRain);
+        character.hp = character.max_hp as i32;
+        let mut battle_info = BattleInfo::begin(&character, &enemies);
+        round(&mut character, &mut enemies, &mut battle_info, &mut rng);
+        assert_eq!(
+            battle_info.number_of_player_attacks,
+            3 * character.action_points()
+        );
+
+        enemies[..2].iter_mut().for_each(|enemy| enemy.health = 0);
+        enemies[2].health = 1;
+        let mut battle_info = BattleInfo::begin(&character, &enemies);
+        round(&mut character, &mut enemies, &mut battle_info, &mut rng);
+        assert!(battle_info.enemy_killed);
+        assert_eq!(battle_info.experience_gained, enemies[2].experience);
+    }
+
+    #[test]
+    fn hp_traits_steal_reflect_and_regenerate() {
+        let mut rng = CarrionRng::new(22);
+        let mut character = Character::new("Vampire".into(), 1, Classes::Warrior);
+        character.available_traits = 3;
+        for tr in [
+            CharacterTraits::Vampiric,
+            CharacterTraits::Thorny,
+            CharacterTraits::Regenerative,
+        ] {
+            character.insert_trait(tr);
+        }
+        character.max_hp = 10_000;
+        character.hp = 1_000;
+        let mut enemies = [Mob::Orc.generate(character.level, &mut rng)];
+        enemies[0].health = 100_000;
+        let mut battle_info = BattleInfo::begin(&character, &enemies);
+
+        let events: Vec<BattleEvent> = (0..5)
+            .flat_map(|_| round(&mut character, &mut enemies, &mut battle_info, &mut rng))
+            .collect();
+        let healed_with = |name: &str| {
+            events.iter().any(|event| match event {
+                BattleEvent::Heal { action, amount, .. } => action == name && *amount > 0,
+                _ => false,
+            })
+        };
+        assert!(healed_with("Regeneration"));
+        assert!(healed_with("Lifesteal"));
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, BattleEvent::Reflect { amount, .. } if *amount > 0)));
+        assert!(battle_info.player_healing > 0);
+    }
+}
@@ -1,18 +1,28 @@
-use crate::class::Classes;
+use crate::class::{Classes, MULTICLASS_LEVEL, PROMOTION_LEVEL};
 
 use crate::enemy::Mob;
 
-use crate::character::Character;
+use crate::character::{Character, Condition};
+use crate::dungeon;
+use crate::encounter::Targeting;
 use crate::r#trait::CharacterTraits;
+use crate::rotation::Rotation;
+use crate::shop::{self, Shop};
 use crate::skill::Skill;
+use crate::unit::ATTRIBUTES;
+use crate::zone::Zone;
 use crate::ValidEnum;
 use crate::{Context, Error};
 
-use crate::item::EquipmentSlot;
+use crate::item::{EquipmentSlot, ItemComparison};
 
 use crate::constructed::ItemsWeHave;
 use crate::game::mutations::Mutations;
+use crate::game::party::LootRule;
+use crate::game::trade::Trade;
 use crate::game_loop::{get_buffer, get_game};
+use poise::serenity_prelude::User;
+use strum::IntoEnumIterator;
 use tracing::{info, warn};
 
 fn tracing_span(user_id: u64, now: tokio::time::Instant, request: String) -> tracing::Span {
@@ -108,7 +118,7 @@ pub async fn create(
     if let Some(class) = class {
         let class = Classes::try_from(class);
         match class {
-            Ok(class) => {
+            Ok(class) if class.is_base() => {
                 let name = ctx.author().name.clone();
                 let id = ctx.author().id.0;
                 let new_character = Character::new(name, id, class);
@@ -121,8 +131,8 @@ pub async fn create(
                     .await
                     .add(Mutations::Create(Box::new(new_character)));
             }
-            Err(_) => {
-                let valid_classes = Classes::valid();
+            _ => {
+                let valid_classes = Classes::starting().join("\n");
                 ctx.send(|b| {
                     b.content(format!(
                         "Invalid class: {:?}\n Valid Classes:\n {}",
@@ -134,7 +144,7 @@ pub async fn create(
             }
         }
     } else {
-        let valid_classes = Classes::valid();
+        let valid_classes = Classes::starting().join("\n");
         ctx.send(|b| {
             b.content(format!(
                 "No class provided\n Valid Classes:\n {}",
@@ -177,19 +187,90 @@ pub async fn me(
     Ok(())
 }
 
-/// Change your skill
+/// Rest between battles to recover your HP
+#[poise::command(prefix_command, slash_command)]
+pub async fn rest(ctx: Context<'_>) -> Result<(), Error> {
+    let now = tokio::time::Instant::now();
+    let user_id = ctx.author().id.0;
+    let reply = match get_game().await.get_character(user_id) {
+        Some(character) => match character.condition {
+            Condition::Dead(_) => format!("You can't rest yet: {}", character.condition),
+            Condition::Resting => {
+                format!("Already resting, HP: {}/{}", character.hp, character.max_hp)
+            }
+            Condition::Ready if character.hp >= character.max_hp as i32 => {
+                "Already at full HP".to_string()
+            }
+            Condition::Ready => {
+                get_buffer().await.add(Mutations::Rest(user_id));
+                format!(
+                    "You set up camp and will fight again once rested, HP: {}/{}",
+                    character.hp, character.max_hp
+                )
+            }
+        },
+        None => "No character found".to_string(),
+    };
+    ctx.send(|b| b.content(reply).ephemeral(true)).await?;
+
+    tracing_span(user_id, now, "rest".to_string());
+    Ok(())
+}
+
+/// Change your skill or rotate through up to four skills
 #[poise::command(prefix_command, slash_command)]
 pub async fn skill(
     ctx: Context<'_>,
     #[autocomplete = "poise::builtins::autocomplete_command"]
     #[description = "Select your skill from the list of valid skills"]
     skill: Option<String>,
+    #[description = "Skills to rotate through, like: priority Mend:hp<30 FireBall:vulnerable Slash"]
+    rotation: Option<String>,
 ) -> Result<(), Error> {
     let now = tokio::time::Instant::now();
     let user_id = ctx.author().id.0;
+    if let Some(rotation) = rotation {
+        let args: Vec<&str> = rotation.split_whitespace().collect();
+        let reply = match (
+            get_game().await.get_character(user_id),
+            Rotation::parse(&args),
+        ) {
+            (None, _) => "No character found".to_string(),
+            (_, Err(e)) => e.to_string(),
+            (Some(mut character), Ok(rotation)) => {
+                match character.set_rotation(rotation.clone(), &[]) {
+                    Ok(()) => {
+                        let skills: Vec<String> =
+                            rotation.skills().map(|skill| skill.to_string()).collect();
+                        let reply =
+                            format!("Rotation set: {:?} {}", rotation.policy, skills.join(", "));
+                        get_buffer()
+                            .await
+                            .add(Mutations::Rotation(user_id, rotation));
+                        reply
+                    }
+                    Err(e) => e.to_string(),
+                }
+            }
+        };
+        ctx.send(|b| b.content(reply).ephemeral(true)).await?;
+        tracing_span(user_id, now, "skill".to_string());
+        return Ok(());
+    }
+    let unlocked = get_game()
+        .await
+        .get_character(user_id)
+        .map(|character| character.ascended)
+        .unwrap_or_default();
     match skill {
         Some(command) => {
-            let skill = Skill::try_from(command);
+            let skill = Skill::try_from(command).and_then(|skill| {
+                if skill.is_ascended() && !unlocked.contains(&skill) {
+                    Err(format!("{} has not been unlocked yet", skill))
+                } else {
+                    Ok(skill)
+                }
+            });
             match skill {
                 Ok(skill) => {
                     get_buffer().await.add(Mutations::Skill(user_id, skill));
@@ -207,7 +288,7 @@ pub async fn skill(
                         b.content(format!(
                             "Invalid skill: {:?}\n Valid Skills:\n {}",
                             err,
-                            Skill::valid()
+                            Skill::valid_for(&unlocked)
                         ))
                         .ephemeral(true)
                     })
@@ -216,7 +297,7 @@ pub async fn skill(
             }
         }
         None => {
-            let valid_skills = Skill::valid();
+            let valid_skills = Skill::valid_for(&unlocked);
             let mut responses = String::new();
             responses.push_str(&format!("Valid Skills:\n {}", valid_skills));
             ctx.send(|b| b.content(responses).ephemeral(true)).await?;
@@ -388,6 +469,603 @@ pub async fn equip(
     Ok(())
 }
 
+/// Inspect an item and compare it with what you have equipped
+#[poise::command(prefix_command, slash_command)]
+pub async fn inspect(
+    ctx: Context<'_>,
+    #[description = "Item to inspect"] item: String,
+) -> Result<(), Error> {
+    let now = tokio::time::Instant::now();
+    let user_id = ctx.author().id.0;
+    let character = get_game().await.get_character(user_id);
+    let items = get_game().await.get_items(user_id).unwrap_or_default();
+    let reply = match character {
+        None => "No character found".to_string(),
+        Some(character) => match ItemComparison::find(&item, &character, &items) {
+            Some(comparison) => comparison.to_string(),
+            None => format!(
+                "Invalid item: {:?}\n Valid Items:\n {}",
+                item,
+                ItemsWeHave::valid()
+            ),
+        },
+    };
+    ctx.send(|b| b.content(reply).ephemeral(true)).await?;
+    tracing_span(user_id, now, "inspect".to_string());
+    Ok(())
+}
+
+/// Browse the shop or buy one of its items
+#[poise::command(prefix_command, slash_command)]
+pub async fn shop(
+    ctx: Context<'_>,
+    #[description = "Number of the item to buy, empty to browse"] item: Option<usize>,
+) -> Result<(), Error> {
+    let now = tokio::time::Instant::now();
+    let user_id = ctx.author().id.0;
+    let shop = Shop::current();
+    let gold = get_game()
+        .await
+        .get_items(user_id)
+        .map(|items| items.gold)
+        .unwrap_or_default();
+    let reply = match item.map(|number| shop.get(number)) {
+        None => format!("{}You have {} gold", shop, gold),
+        Some(None) => format!("There is no item {} in the shop", item.unwrap_or_default()),
+        Some(Some(item)) if gold < shop::price(item.rarity) => format!(
+            "{} costs {} gold, you have {}",
+            item.name,
+            shop::price(item.rarity),
+            gold
+        ),
+        Some(Some(item)) => {
+            get_buffer()
+                .await
+                .add(Mutations::Buy(user_id, item.clone()));
+            format!("Buying {} for {} gold", item.name, shop::price(item.rarity))
+        }
+    };
+    ctx.send(|b| b.content(reply).ephemeral(true)).await?;
+    tracing_span(user_id, now, "shop".to_string());
+    Ok(())
+}
+
+/// Pay the gambler for an item of random slot and rarity
+#[poise::command(prefix_command, slash_command)]
+pub async fn gamble(ctx: Context<'_>) -> Result<(), Error> {
+    let now = tokio::time::Instant::now();
+    let user_id = ctx.author().id.0;
+    let gold = get_game()
+        .await
+        .get_items(user_id)
+        .map(|items| items.gold)
+        .unwrap_or_default();
+    let reply = if gold < shop::GAMBLE_COST {
+        format!(
+            "The gambler wants {} gold, you have {}",
+            shop::GAMBLE_COST,
+            gold
+        )
+    } else {
+        get_buffer().await.add(Mutations::Gamble(user_id));
+        format!(
+            "You hand the gambler {} gold, your winnings will show up in your items",
+            shop::GAMBLE_COST
+        )
+    };
+    ctx.send(|b| b.content(reply).ephemeral(true)).await?;
+    tracing_span(user_id, now, "gamble".to_string());
+    Ok(())
+}
+
+/// Offer items and gold to another player, or accept and decline their offers
+#[poise::command(prefix_command, slash_command)]
+pub async fn trade(
+    ctx: Context<'_>,
+    #[description = "offer, accept, decline or cancel, empty to list"] action: Option<String>,
+    #[description = "Player to trade with"] user: Option<User>,
+    #[description = "Item to offer"] item: Option<String>,
+    #[description = "Gold to offer"] gold: Option<u64>,
+) -> Result<(), Error> {
+    let now = tokio::time::Instant::now();
+    let user_id = ctx.author().id.0;
+    let game = get_game().await;
+    let other = user.as_ref().map(|user| user.id.0);
+    let reply = match (game.get_items(user_id), action.as_deref(), other) {
+        (None, _, _) => "No character found".to_string(),
+        (Some(_), None, _) => {
+            let trades = game.get_trades(user_id);
+            match trades.is_empty() {
+                true => "You have no open trades".to_string(),
+                false => trades
+                    .iter()
+                    .map(|trade| match trade.from == user_id {
+                        true => format!("You offered {} to <@{}>", trade, trade.to),
+                        false => format!("<@{}> offers you {}", trade.from, trade),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            }
+        }
+        (Some(_), Some("offer"), Some(to)) if game.get_items(to).is_none() => {
+            "They have no character".to_string()
+        }
+        (Some(_), Some("offer"), _) if game.get_trade(user_id).is_some() => {
+            "You already have an open offer, cancel it first".to_string()
+        }
+        (Some(items), Some("offer"), Some(to)) => {
+            let gold = gold.unwrap_or_default();
+            // Dry run on a copy, the mutation takes the real items into escrow
+            match Trade::offer(user_id, to, item.as_deref(), gold, &mut items.clone()) {
+                Ok(trade) => {
+                    get_buffer()
+                        .await
+                        .add(Mutations::OfferTrade(user_id, to, item, gold));
+                    format!(
+                        "Offering {} to <@{}>, they can /trade accept or decline",
+                        trade, to
+                    )
+                }
+                Err(e) => e.to_string(),
+            }
+        }
+        (Some(_), Some("accept"), Some(from))
+            if game.get_trade(from).is_some_and(|t| t.to == user_id) =>
+        {
+            get_buffer()
+                .await
+                .add(Mutations::AcceptTrade(user_id, from));
+            format!("Accepting the trade from <@{}>", from)
+        }
+        (Some(_), Some("decline"), Some(from))
+            if game.get_trade(from).is_some_and(|t| t.to == user_id) =>
+        {
+            get_buffer()
+                .await
+                .add(Mutations::DeclineTrade(user_id, from));
+            format!("Declining the trade from <@{}>", from)
+        }
+        (Some(_), Some("cancel"), _) if game.get_trade(user_id).is_some() => {
+            get_buffer()
+                .await
+                .add(Mutations::DeclineTrade(user_id, user_id));
+            "Cancelling your offer".to_string()
+        }
+        (Some(_), Some("accept" | "decline"), _) => "They have not offered you a trade".to_string(),
+        (Some(_), Some("cancel"), _) => "You have no open offer".to_string(),
+        _ => {
+            "Usage: /trade offer @user [item] [gold], /trade accept | decline @user, /trade cancel"
+                .to_string()
+        }
+    };
+    ctx.send(|b| b.content(reply).ephemeral(true)).await?;
+    tracing_span(user_id, now, "trade".to_string());
+    Ok(())
+}
+
+/// Spend a trait point on a permanent attribute boost, empty to list the boosts
+#[poise::command(prefix_command, slash_command)]
+pub async fn boost(
+    ctx: Context<'_>,
+    #[description = "Attribute to boost"] attribute: Option<String>,
+) -> Result<(), Error> {
+    let now = tokio::time::Instant::now();
+    let user_id = ctx.author().id.0;
+    let character = get_game().await.get_character(user_id);
+    let reply = match (character, attribute) {
+        (None, _) => "No character found".to_string(),
+        (Some(character), None) => {
+            let boosts = ATTRIBUTES
+                .iter()
+                .map(|attribute| {
+                    format!("{}: +{}", attribute, character.attribute_boost(attribute))
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            format!(
+                "You have {} trait points\n```\n{}\n```",
+                character.available_traits, boosts
+            )
+        }
+        (Some(mut character), Some(attribute)) => match character.boost_attribute(&attribute) {
+            Ok(boost) => {
+                get_buffer()
+                    .await
+                    .add(Mutations::BoostAttribute(user_id, attribute.to_lowercase()));
+                format!("Boosting {} by {}", attribute.to_lowercase(), boost)
+            }
+            Err(e) => e.to_string(),
+        },
+    };
+    ctx.send(|b| b.content(reply).ephemeral(true)).await?;
+    tracing_span(user_id, now, "boost".to_string());
+    Ok(())
+}
+
+/// Pay gold to take back your traits and attribute boosts and get their points back
+#[poise::command(prefix_command, slash_command)]
+pub async fn respec(ctx: Context<'_>) -> Result<(), Error> {
+    let now = tokio::time::Instant::now();
+    let user_id = ctx.author().id.0;
+    let game = get_game().await;
+    let reply = match (game.get_character(user_id), game.get_items(user_id)) {
+        (Some(mut character), Some(mut items)) => {
+            // Dry run on copies, the mutation respecs the real character
+            match character.respec(&mut items) {
+                Ok(refund) => {
+                    get_buffer().await.add(Mutations::Respec(user_id));
+                    format!(
+                        "Paying {} gold to get {} trait points back",
+                        character.respec_cost(),
+                        refund
+                    )
+                }
+                Err(e) => e.to_string(),
+            }
+        }
+        _ => "No character found".to_string(),
+    };
+    ctx.send(|b| b.content(reply).ephemeral(true)).await?;
+    tracing_span(user_id, now, "respec".to_string());
+    Ok(())
+}
+
+/// Raid bosses together with other players, empty to show your party
+#[poise::command(prefix_command, slash_command)]
+pub async fn party(
+    ctx: Context<'_>,
+    #[description = "create, invite, join, leave or raid, empty to show"] action: Option<String>,
+    #[description = "Player to invite, or whose party to join"] user: Option<User>,
+    #[description = "How items are looted when creating a party"] loot: Option<String>,
+) -> Result<(), Error> {
+    let now = tokio::time::Instant::now();
+    let user_id = ctx.author().id.0;
+    let game = get_game().await;
+    let other = user.as_ref().map(|user| user.id.0);
+    let party = game.get_party(user_id);
+    let reply = match (game.get_character(user_id), action.as_deref(), party) {
+        (None, _, _) => "No character found".to_string(),
+        (Some(_), None, Some(party)) => party.to_string(),
+        (Some(_), None, None) => "You are not in a party".to_string(),
+        (Some(_), Some("create"), Some(_)) => "You are already in a party".to_string(),
+        (Some(_), Some("create"), None) => match loot.map(LootRule::try_from).transpose() {
+            Ok(loot) => {
+                let loot = loot.unwrap_or_default();
+                get_buffer()
+                    .await
+                    .add(Mutations::CreateParty(user_id, loot));
+                format!("Creating a party with loot by {}", loot)
+            }
+            Err(_) => format!("Invalid loot rule, valid rules:\n{}", LootRule::valid()),
+        },
+        (Some(_), Some("invite"), Some(party)) => match other {
+            Some(to) if game.get_character(to).is_none() => "They have no character".to_string(),
+            Some(to) if game.get_party(to).is_some() => "They are already in a party".to_string(),
+            Some(to) => match party.clone().invite(user_id, to) {
+                Ok(()) => {
+                    get_buffer().await.add(Mutations::InviteParty(user_id, to));
+                    format!("Inviting <@{}>, they can /party join @you", to)
+                }
+                Err(e) => e.to_string(),
+            },
+            None => "Usage: /party invite @user".to_string(),
+        },
+        (Some(_), Some("join"), Some(_)) => "You are already in a party".to_string(),
+        (Some(_), Some("join"), None) => match other.and_then(|member| game.get_party(member)) {
+            Some(party) => match party.clone().join(user_id) {
+                Ok(()) => {
+                    get_buffer()
+                        .await
+                        .add(Mutations::JoinParty(user_id, party.leader));
+                    format!("Joining the party of <@{}>", party.leader)
+                }
+                Err(e) => e.to_string(),
+            },
+            None => "They are not in a party".to_string(),
+        },
+        (Some(_), Some("leave"), Some(_)) => {
+            get_buffer().await.add(Mutations::LeaveParty(user_id));
+            "Leaving your party".to_string()
+        }
+        (Some(_), Some("raid"), Some(party)) => {
+            // The boss is spawned by the mutation
+            match party.can_raid(user_id) {
+                Ok(()) => {
+                    get_buffer().await.add(Mutations::StartRaid(user_id));
+                    "Gathering the party, the raid starts on your next battle".to_string()
+                }
+                Err(e) => e.to_string(),
+            }
+        }
+        (Some(_), Some("invite" | "leave" | "raid"), None) => "You are not in a party".to_string(),
+        _ => "Usage: /party create [loot], /party invite | join @user, /party leave | raid"
+            .to_string(),
+    };
+    ctx.send(|b| b.content(reply).ephemeral(true)).await?;
+    tracing_span(user_id, now, "party".to_string());
+    Ok(())
+}
+
+/// Pick the zone you hunt in, empty to list them
+#[poise::command(prefix_command, slash_command)]
+pub async fn zone(
+    ctx: Context<'_>,
+    #[description = "Zone to hunt in"] zone: Option<String>,
+) -> Result<(), Error> {
+    let now = tokio::time::Instant::now();
+    let user_id = ctx.author().id.0;
+    let character = get_game().await.get_character(user_id);
+    let reply = match (character, zone.map(Zone::try_from)) {
+        (None, _) => "No character found".to_string(),
+        (Some(character), None) => {
+            let zones = Zone::iter()
+                .map(|zone| zone.description())
+                .collect::<Vec<_>>()
+                .join("\n");
+            format!("You hunt in {}\n```\n{}\n```", character.zone, zones)
+        }
+        (Some(_), Some(Err(_))) => format!("Invalid zone, valid zones:\n{}", Zone::valid()),
+        (Some(character), Some(Ok(_))) if character.dungeon.is_some() => {
+            "You are in a dungeon, abandon it first".to_string()
+        }
+        (Some(character), Some(Ok(zone))) if !zone.can_enter(character.level) => format!(
+            "{} needs level {}, you are level {}",
+            zone,
+            zone.min_level(),
+            character.level
+        ),
+        (Some(_), Some(Ok(zone))) => {
+            get_buffer().await.add(Mutations::Zone(user_id, zone));
+            format!("Travelling to {}", zone)
+        }
+    };
+    ctx.send(|b| b.content(reply).ephemeral(true)).await?;
+    tracing_span(user_id, now, "zone".to_string());
+    Ok(())
+}
+
+/// Pick which enemy of an encounter your skills hit, empty to list the rules
+#[poise::command(prefix_command, slash_command)]
+pub async fn target(
+    ctx: Context<'_>,
+    #[description = "Targeting rule for single target skills"] targeting: Option<String>,
+) -> Result<(), Error> {
+    let now = tokio::time::Instant::now();
+    let user_id = ctx.author().id.0;
+    let character = get_game().await.get_character(user_id);
+    let reply = match (character, targeting.map(Targeting::try_from)) {
+        (None, _) => "No character found".to_string(),
+        (Some(character), None) => format!(
+            "You target {}, area skills hit every enemy\nValid rules:\n{}",
+            character.targeting,
+            Targeting::valid()
+        ),
+        (Some(_), Some(Err(_))) => format!("Invalid rule, valid rules:\n{}", Targeting::valid()),
+        (Some(_), Some(Ok(targeting))) => {
+            get_buffer()
+                .await
+                .add(Mutations::Targeting(user_id, targeting));
+            format!("Now targeting {}", targeting)
+        }
+    };
+    ctx.send(|b| b.content(reply).ephemeral(true)).await?;
+    tracing_span(user_id, now, "target".to_string());
+    Ok(())
+}
+
+/// Promote your class once you reach the promotion level, empty to list your promotions
+#[poise::command(prefix_command, slash_command)]
+pub async fn promote(
+    ctx: Context<'_>,
+    #[description = "Class to be promoted to"] class: Option<String>,
+) -> Result<(), Error> {
+    let now = tokio::time::Instant::now();
+    let user_id = ctx.author().id.0;
+    let character = get_game().await.get_character(user_id);
+    let reply = match (character, class.map(Classes::try_from)) {
+        (None, _) => "No character found".to_string(),
+        (Some(character), None) if character.class.promotions().is_empty() => {
+            format!("You have already been promoted to {}", character.class)
+        }
+        (Some(character), None | Some(Err(_))) => {
+            let promotions = character
+                .class
+                .promotions()
+                .iter()
+                .map(|class| format!("{} - {}", class, class.action()))
+                .collect::<Vec<_>>()
+                .join("\n");
+            format!(
+                "A {} can be promoted at level {} to:\n{}",
+                character.class, PROMOTION_LEVEL, promotions
+            )
+        }
+        (Some(mut character), Some(Ok(class))) => match character.promote(class) {
+            Ok(()) => {
+                get_buffer().await.add(Mutations::Promote(user_id, class));
+                format!(
+                    "You are now a {}, /skill {:?} to use its class skill",
+                    class,
+                    class.action()
+                )
+            }
+            Err(e) => e.to_string(),
+        },
+    };
+    ctx.send(|b| b.content(reply).ephemeral(true)).await?;
+    tracing_span(user_id, now, "promote".to_string());
+    Ok(())
+}
+
+/// Pick a secondary class for its skills and some of its HP and armor, empty to list them
+#[poise::command(prefix_command, slash_command)]
+pub async fn multiclass(
+    ctx: Context<'_>,
+    #[description = "Secondary class"] class: Option<String>,
+) -> Result<(), Error> {
+    let now = tokio::time::Instant::now();
+    let user_id = ctx.author().id.0;
+    let character = get_game().await.get_character(user_id);
+    let reply = match (character, class.map(Classes::try_from)) {
+        (None, _) => "No character found".to_string(),
+        (Some(character), None | Some(Err(_))) => {
+            let classes = character
+                .secondary_classes()
+                .iter()
+                .map(|class| class.to_string())
+                .collect::<Vec<_>>()
+                .join("\n");
+            format!(
+                "From level {} you can pick a secondary class:\n{}",
+                MULTICLASS_LEVEL, classes
+            )
+        }
+        (Some(mut character), Some(Ok(class))) => match character.multiclass(class) {
+            Ok(()) => {
+                get_buffer()
+                    .await
+                    .add(Mutations::Multiclass(user_id, class));
+                format!("You are now also a {}", class)
+            }
+            Err(e) => e.to_string(),
+        },
+    };
+    ctx.send(|b| b.content(reply).ephemeral(true)).await?;
+    tracing_span(user_id, now, "multiclass".to_string());
+    Ok(())
+}
+
+/// Run a dungeon of 10 to 20 mobs ending in a boss, the loot only drops if you clear it
+#[poise::command(
+    prefix_command,
+    slash_command,
+    subcommands("dungeon_enter", "dungeon_status", "dungeon_abandon")
+)]
+pub async fn dungeon(ctx: Context<'_>) -> Result<(), Error> {
+    dungeon_status_reply(ctx).await
+}
+
+/// Pay the entry fee and start a dungeon run
+#[poise::command(prefix_command, slash_command, rename = "enter")]
+pub async fn dungeon_enter(ctx: Context<'_>) -> Result<(), Error> {
+    let now = tokio::time::Instant::now();
+    let user_id = ctx.author().id.0;
+    let character = get_game().await.get_character(user_id);
+    let items = get_game().await.get_items(user_id).unwrap_or_default();
+    let reply = match character {
+        None => "No character found".to_string(),
+        Some(character) if character.dungeon.is_some() => {
+            "You are already in a dungeon, abandon it first".to_string()
+        }
+        Some(character) if character.level < dungeon::MIN_LEVEL => {
+            format!("Dungeons need level {}", dungeon::MIN_LEVEL)
+        }
+        Some(_) if items.gold < dungeon::ENTRY_FEE => format!(
+            "{} gold is needed to enter, you have {}",
+            dungeon::ENTRY_FEE,
+            items.gold
+        ),
+        Some(_) => {
+            get_buffer().await.add(Mutations::EnterDungeon(user_id));
+            format!(
+                "Paying {} gold to enter the dungeon, clear every mob and the boss for its loot",
+                dungeon::ENTRY_FEE
+            )
+        }
+    };
+    ctx.send(|b| b.content(reply).ephemeral(true)).await?;
+    tracing_span(user_id, now, "dungeon enter".to_string());
+    Ok(())
+}
+
+/// Show how far into your dungeon run you are
+#[poise::command(prefix_command, slash_command, rename = "status")]
+pub async fn dungeon_status(ctx: Context<'_>) -> Result<(), Error> {
+    dungeon_status_reply(ctx).await
+}
+
+async fn dungeon_status_reply(ctx: Context<'_>) -> Result<(), Error> {
+    let now = tokio::time::Instant::now();
+    let user_id = ctx.author().id.0;
+    let character = get_game().await.get_character(user_id);
+    let reply = match character.map(|c| c.dungeon) {
+        None => "No character found".to_string(),
+        Some(None) => format!(
+            "You are not in a dungeon, entering costs {} gold from level {}",
+            dungeon::ENTRY_FEE,
+            dungeon::MIN_LEVEL
+        ),
+        Some(Some(run)) => run.to_string(),
+    };
+    ctx.send(|b| b.content(reply).ephemeral(true)).await?;
+    tracing_span(user_id, now, "dungeon status".to_string());
+    Ok(())
+}
+
+/// Leave your dungeon run, the entry fee is lost
+#[poise::command(prefix_command, slash_command, rename = "abandon")]
+pub async fn dungeon_abandon(ctx: Context<'_>) -> Result<(), Error> {
+    let now = tokio::time::Instant::now();
+    let user_id = ctx.author().id.0;
+    let character = get_game().await.get_character(user_id);
+    let reply = match character.map(|c| c.dungeon) {
+        None => "No character found".to_string(),
+        Some(None) => "You are not in a dungeon".to_string(),
+        Some(Some(run)) => {
+            get_buffer().await.add(Mutations::AbandonDungeon(user_id));
+            format!("Abandoning the dungeon after {} mobs", run.cleared)
+        }
+    };
+    ctx.send(|b| b.content(reply).ephemeral(true)).await?;
+    tracing_span(user_id, now, "dungeon abandon".to_string());
+    Ok(())
+}
+
+/// Save, swap between or list your equipment loadouts
+#[poise::command(prefix_command, slash_command)]
+pub async fn loadout(
+    ctx: Context<'_>,
+    #[description = "save, swap or delete, empty to list them"] action: Option<String>,
+    #[description = "Name of the loadout"] name: Option<String>,
+) -> Result<(), Error> {
+    let now = tokio::time::Instant::now();
+    let user_id = ctx.author().id.0;
+    let character = get_game().await.get_character(user_id);
+    let reply = match (character, action.as_deref(), name) {
+        (None, _, _) => "No character found".to_string(),
+        (Some(_), Some("save"), Some(name)) => {
+            get_buffer()
+                .await
+                .add(Mutations::SaveLoadout(user_id, name.clone()));
+            format!("Saving your equipment as {name}")
+        }
+        (Some(character), Some("swap"), Some(name)) if character.loadouts.get(&name).is_some() => {
+            get_buffer()
+                .await
+                .add(Mutations::SwapLoadout(user_id, name.clone()));
+            format!("Swapping to {name}")
+        }
+        (Some(character), Some("delete"), Some(name))
+            if character.loadouts.get(&name).is_some() =>
+        {
+            get_buffer()
+                .await
+                .add(Mutations::DeleteLoadout(user_id, name.clone()));
+            format!("Deleting {name}, its items can be sold again")
+        }
+        (Some(_), Some("swap" | "delete"), Some(name)) => format!("No loadout named {name}"),
+        (Some(_), Some(_), _) => "Use save, swap or delete with the name of a loadout".to_string(),
+        (Some(character), None, _) if character.loadouts.names().next().is_none() => {
+            "No loadouts saved, use save with a name to save your equipment".to_string()
+        }
+        (Some(character), None, _) => format!("```{}```", character.loadouts),
+    };
+    ctx.send(|b| b.content(reply).ephemeral(true)).await?;
+
+    tracing_span(user_id, now, "loadout".to_string());
+    Ok(())
+}
+
 /// Battle an enemy
 #[poise::command(prefix_command, slash_command)]
 pub async fn battle(
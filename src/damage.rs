@@ -4,37 +4,49 @@ use derive_builder::Builder;
 use rand::distributions::Standard;
 use rand::prelude::Distribution;
 use rand::seq::SliceRandom;
-use rand::{thread_rng, Rng};
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 use std::ops::{Add, AddAssign};
 use strum::{EnumIter, IntoEnumIterator};
 
+use crate::combat::heal_player;
+use crate::status::StatusEffect;
 use crate::unit::Alignment;
 use crate::BattleInfo;
 use crate::{armor_scaling, character::Character, dodge_scaling, enemy::Enemy, resistance_scaling};
+/// Extra damage a hit of the type the defender is vulnerable to deals after mitigation.
+pub const SUPER_EFFECTIVE_BONUS: f64 = 0.5;
+/// Extra damage an aligned hit deals to a defender of the opposing alignment.
+pub const ALIGNMENT_BONUS: f64 = 0.25;
+
 #[derive(Default, Debug)]
 
 pub struct Defense {
     dodge: i32,
     armor: i32,
     suppress: HashMap<ResistCategories, i32>,
+    vulnerability: Option<ResistCategories>,
+    alignment: Option<Alignment>,
 }
 
 impl Defense {
     pub fn new_enemy(enemy: &Enemy) -> Self {
-        let mut suppress = ResistCategories::resist_category_hash_map();
-        suppress.insert(ResistCategories::Universal, enemy.resistance);
+        let suppress = enemy.kind.resistances(enemy.resistance);
         let armor = enemy.defense;
         let dodge = crate::EnemyEvents::grade(&enemy.kind) as i32;
+        let vulnerability =
+            crate::EnemyEvents::vulnerability(&enemy.kind).map(|dtype| dtype.resist_category());
         Self {
             dodge,
             armor,
             suppress,
+            vulnerability,
+            alignment: Some(crate::EnemyEvents::alignment(&enemy.kind)),
         }
     }
 
     pub fn new(character: &Character) -> Self {
-        let suppress: HashMap<_, _> = character
+        let mut suppress: HashMap<_, _> = character
             .mutations()
             .get_all_suppress()
             .iter()
@@ -44,19 +56,33 @@ impl Defense {
                 *acc.entry(key).or_insert(0) += value;
                 acc
             });
-        let armor = character.equipment.armor() + character.mutations().get_armor();
-        let dodge = character.equipment.dodge() + character.mutations().get_dodge();
+        if character.buffs.ward() > 0 {
+            *suppress.entry(ResistCategories::Universal).or_insert(0) += character.buffs.ward();
+        }
+        let armor = character.equipment.armor()
+            + character.mutations().get_armor()
+            + character.buffs.armor();
+        let dodge = (character.equipment.dodge()
+            + character.mutations().get_dodge()
+            + character.buffs.dodge())
+        .max(0);
 
         Self {
             dodge,
             armor,
             suppress,
+            vulnerability: None,
+            alignment: None,
         }
     }
 
-    pub fn dodge(&self) -> bool {
-        let dodge_probability = dodge_scaling(self.dodge).min(85.0) / 100.0;
-        thread_rng().gen_bool(dodge_probability)
+    pub fn dodge(&self, rng: &mut impl Rng) -> bool {
+        rng.gen_bool(self.dodge_chance() / 100.0)
+    }
+
+    /// Chance to dodge a hit in percent, capped at 85.
+    pub fn dodge_chance(&self) -> f64 {
+        dodge_scaling(self.dodge).min(85.0)
     }
 
     pub fn physical_mitigation(&self) -> f64 {
@@ -90,6 +116,24 @@ impl Defense {
         }
     }
 
+    pub fn super_effective(&self, resist: ResistCategories) -> bool {
+        self.vulnerability == Some(resist)
+    }
+
+    /// Share of extra damage `damage` deals for hitting a vulnerability or an opposing alignment.
+    pub fn weakness(&self, damage: &Damage) -> f64 {
+        let mut weakness = 0.0;
+        if self.super_effective(damage.dtype().resist_category()) {
+            weakness += SUPER_EFFECTIVE_BONUS;
+        }
+        if let (Some(alignment), Some(defender)) = (damage.alignment, self.alignment) {
+            if alignment.opposes(defender) {
+                weakness += ALIGNMENT_BONUS;
+            }
+        }
+        weakness
+    }
+
     pub fn defense(&self, resist: ResistCategories) -> f64 {
         match resist {
             ResistCategories::Physical => (self.physical_mitigation()).min(99.8),
@@ -206,6 +250,7 @@ impl From<&str> for DamageType {
             "prismatic" => DamageType::Prismatic,
             "universal" => DamageType::Universal,
             "elemental" => DamageType::Elemental,
+            "healing" => DamageType::Healing,
             _ => panic!("Invalid Damage Type {s:?}"),
         }
     }
@@ -227,44 +272,73 @@ pub enum UniqueDamageEffect {
 }
 
 impl UniqueDamageEffect {
+    fn status(&self, tick: u32) -> StatusEffect {
+        StatusEffect::new(*self, tick as i32).expect("Effect has no duration")
+    }
+
+    /// Leaves the lingering part of an enemy's hit on the character, the way `apply` does for
+    /// the character's hits on an enemy.
+    pub fn afflict(&self, player: &mut Character, self_damage: &Damage) {
+        use UniqueDamageEffect::*;
+        let tick = match self {
+            Poison | Bleed => (player.max_hp / 50).max(1),
+            Burn => (player.max_hp / 30).max(1),
+            Shock | Curse => 0,
+            _ => return,
+        };
+        let status = self.status(tick);
+        player.status.apply(match self {
+            Bleed => status.with_stacks(self_damage.number_of_hits),
+            _ => status,
+        });
+    }
+
     pub fn apply(
         &self,
-        player: &Character,
-        enemy: &Enemy,
+        player: &mut Character,
+        enemy: &mut Enemy,
         self_damage: &Damage,
         battle_info: &mut BattleInfo,
+        rng: &mut impl Rng,
     ) -> Damage {
         let mut damage = Damage::zero(self_damage.dtype);
         use UniqueDamageEffect::*;
         match self {
             Poison => {
-                damage.damage += enemy.health / 10;
+                enemy
+                    .status
+                    .apply(self.status((enemy.max_health() / 50).max(1)));
                 battle_info.custom_text = Some("Poisoned".to_string());
             }
             Bleed => {
-                damage.damage += (enemy.health / 10) * self_damage.number_of_hits as i32;
+                let bleed = self.status((enemy.max_health() / 50).max(1));
+                enemy
+                    .status
+                    .apply(bleed.with_stacks(self_damage.number_of_hits));
                 battle_info.custom_text = Some("Bleeding".to_string());
             }
             Burn => {
-                damage.damage += enemy.health / 10;
+                enemy
+                    .status
+                    .apply(self.status((enemy.max_health() / 30).max(1)));
                 battle_info.custom_text = Some("Burning".to_string());
             }
             Shock => {
-                damage.multiplier = 1.5;
+                enemy.status.apply(self.status(0));
                 battle_info.custom_text = Some("Shocked".to_string());
             }
             Curse => {
-                damage.multiplier = 1.5;
+                enemy.status.apply(self.status(0));
                 battle_info.custom_text = Some("Cursed".to_string());
             }
             Regenerate => {
-                battle_info.player_healing += (player.max_hp / 3u32) as i32;
-                battle_info.player_healing =
-                    battle_info.player_healing.min(battle_info.enemy_damage);
+                player
+                    .status
+                    .apply(self.status((player.max_hp / 12).max(1)));
                 battle_info.custom_text = Some("Regenerating".to_string());
             }
             Invigorate => {
-                battle_info.player_healing += (player.max_hp) as i32;
+                heal_player(player, player.max_hp as i32, battle_info);
                 battle_info.custom_text = Some("Invigorating".to_string());
             }
             Enrage => {
@@ -278,57 +352,16 @@ impl UniqueDamageEffect {
                 battle_info.custom_text = Some("Berserk".to_string());
             }
             Vampire => {
-                battle_info.player_healing += damage.damage() / 4;
+                heal_player(player, damage.damage(rng) / 4, battle_info);
                 battle_info.custom_text = Some("Vampiric".to_string());
             }
             Death => {
-                battle_info.enemy_killed = true;
+                enemy.state = crate::enemy::EnemyState::Dead;
                 battle_info.custom_text = Some("Instant Death".to_string());
             }
         }
         damage
     }
-
-    fn apply_cli(&self, player: &mut Character, enemy: &mut Enemy, self_damage: &Damage) -> Damage {
-        let mut damage = Damage::zero(self_damage.dtype);
-        use UniqueDamageEffect::*;
-        match self {
-            Poison => {
-                damage.damage += enemy.health / 10;
-            }
-            Bleed => {
-                damage.damage += (enemy.health / 10) * self_damage.number_of_hits as i32;
-            }
-            Burn => {
-                damage.damage += enemy.health / 10;
-            }
-            Shock => {
-                damage.multiplier = 1.5;
-            }
-            Curse => {
-                damage.multiplier = 1.5;
-            }
-            Regenerate => {
-                player.hp += (player.max_hp / 3u32) as i32;
-            }
-            Invigorate => {
-                player.hp += (player.max_hp) as i32;
-            }
-            Enrage => {
-                damage.crit_chance = 0.25;
-                damage.critical_multiplier += 1.5;
-            }
-            Berserk => {
-                damage.number_of_hits += 2;
-                damage.number_of_hits = (self_damage.number_of_hits as f64 * 1.25) as u32;
-            }
-            Vampire => {
-                player.hp += damage.damage() / 4;
-            }
-            Death => enemy.state = crate::enemy::EnemyState::Dead,
-        }
-        damage
-    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default, Builder)]
@@ -351,37 +384,37 @@ pub struct Damage {
 }
 
 impl Damage {
-    pub fn damage(&self) -> i32 {
+    pub fn damage(&self, rng: &mut impl Rng) -> i32 {
+        self.roll(rng).0
+    }
+
+    /// Rolls the damage of every hit, returning the total and how many hits were critical.
+    pub fn roll(&self, rng: &mut impl Rng) -> (i32, u32) {
         let mut damage: i32 = 0;
+        let mut crits = 0;
 
         for _ in 0..self.number_of_hits {
             damage = damage.saturating_add(self.damage);
-            if thread_rng().gen_bool(self.crit_chance) {
+            if rng.gen_bool(self.crit_chance.clamp(0.0, 1.0)) {
+                crits += 1;
                 damage =
                     damage.saturating_add((self.damage as f64 * self.critical_multiplier) as i32);
             }
         }
         damage = damage.saturating_add((damage as f64 * self.multiplier) as i32);
-        damage
+        (damage, crits)
     }
 
-    pub fn appy_unique_effects(
+    pub fn apply_unique_effects(
         &mut self,
-        player: &Character,
-        enemy: &Enemy,
+        player: &mut Character,
+        enemy: &mut Enemy,
         battle_info: &mut BattleInfo,
+        rng: &mut impl Rng,
     ) {
         let mut damage = Damage::zero(self.dtype);
         for effect in self.unique_effect.iter() {
-            let mutated_damage = effect.apply(player, enemy, self, battle_info);
-            damage += mutated_damage;
-        }
-        *self += damage;
-    }
-    pub(crate) fn apply_unique_cli(&mut self, arg: &mut Character, enemy: &mut Enemy) {
-        let mut damage = Damage::zero(self.dtype);
-        for effect in self.unique_effect.iter() {
-            let mutated_damage = effect.apply_cli(arg, enemy, self);
+            let mutated_damage = effect.apply(player, enemy, self, battle_info, rng);
             damage += mutated_damage;
         }
         *self += damage;
@@ -434,6 +467,7 @@ impl Add for Damage {
 #[cfg(test)]
 mod test {
     use crate::enemy::Mob;
+    use crate::rng::CarrionRng;
 
     use super::*;
 
@@ -442,7 +476,7 @@ mod test {
         let mut charater: Character = Default::default();
         charater.level = 10;
         let enemy: Mob = Mob::Orc;
-        let enemy: Enemy = enemy.generate(charater.level);
+        let enemy: Enemy = enemy.generate(charater.level, &mut CarrionRng::new(10));
         let defense: Defense = (&enemy).into();
         assert!(
             dodge_scaling(defense.dodge) / 100.0 < 0.06,
@@ -450,4 +484,28 @@ mod test {
             dodge_scaling(defense.dodge) / 100.0
         )
     }
+
+    #[test]
+    fn vulnerabilities_and_alignments_take_extra_damage() {
+        let elf = Mob::Elf.generate(10, &mut CarrionRng::new(13));
+        let defense: Defense = (&elf).into();
+        assert!(defense.super_effective(ResistCategories::Physical));
+        assert!(
+            defense.defense(ResistCategories::Elemental)
+                > defense.defense(ResistCategories::NonElemental)
+        );
+
+        let mut damage = Damage::zero(DamageType::Physical);
+        assert_eq!(defense.weakness(&damage), SUPER_EFFECTIVE_BONUS);
+        damage.alignment = Some(Alignment::LawfulGood);
+        assert_eq!(defense.weakness(&damage), SUPER_EFFECTIVE_BONUS);
+        damage.alignment = Some(Alignment::ChaoticEvil);
+        assert_eq!(
+            defense.weakness(&damage),
+            SUPER_EFFECTIVE_BONUS + ALIGNMENT_BONUS
+        );
+
+        let player_defense = Defense::new(&Character::default());
+        assert_eq!(player_defense.weakness(&damage), 0.0);
+    }
 }
@@ -2,12 +2,14 @@ use crate::{
     character::Character,
     database::{mock::MockDatabase, Consumer},
     enemy::{Enemy, Mob},
+    game::trade::Trade,
     item::Items,
-    skill::SkillSet,
+    skill::{Skill, SkillSet},
     CarrionResult,
 };
 use async_trait::async_trait;
 
+use strum::IntoEnumIterator;
 use tracing::{instrument, Level};
 
 pub struct MockConsumer {}
@@ -70,7 +72,23 @@ impl Consumer for MockConsumer {
             .map(|s| s.clone()))
     }
     #[instrument(skip(self), target = "database_consumer", ret(level = Level::TRACE))]
+    async fn get_ascended_skills(&self, user_id: u64) -> CarrionResult<Vec<Skill>> {
+        let skills = &MockDatabase::get().skills;
+        Ok(Skill::iter()
+            .filter(|skill| skill.is_ascended())
+            .filter(|skill| skills.contains_key(&(user_id, *skill as u64)))
+            .collect())
+    }
+    #[instrument(skip(self), target = "database_consumer", ret(level = Level::TRACE))]
     async fn get_items(&self, user_id: u64) -> CarrionResult<Option<Items>> {
         Ok(MockDatabase::get().items.get(&user_id).map(|i| i.clone()))
     }
+    #[instrument(skip(self), target = "database_consumer", ret(level = Level::TRACE))]
+    async fn get_trades(&self) -> CarrionResult<Vec<Trade>> {
+        Ok(MockDatabase::get()
+            .trades
+            .iter()
+            .map(|t| t.clone())
+            .collect())
+    }
 }
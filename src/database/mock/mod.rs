@@ -1,6 +1,7 @@
 use crate::{
     character::Character,
     enemy::{Enemy, Mob},
+    game::trade::Trade,
     item::Items,
     skill::SkillSet,
 };
@@ -20,6 +21,7 @@ pub struct MockDatabase {
     pub skills: DashMap<(u64, u64), SkillSet>,
     pub items: DashMap<u64, Items>,
     pub current_skill: DashMap<u64, SkillSet>,
+    pub trades: DashMap<u64, Trade>,
 }
 
 impl<'de> Deserialize<'de> for MockDatabase {
@@ -35,6 +37,8 @@ impl<'de> Deserialize<'de> for MockDatabase {
             skills: Vec<((u64, u64), SkillSet)>,
             items: Vec<(u64, Items)>,
             current_skill: Vec<(u64, SkillSet)>,
+            #[serde(default)]
+            trades: Vec<(u64, Trade)>,
         }
 
         let helper = MockDatabaseHelper::deserialize(deserializer)?;
@@ -59,6 +63,9 @@ impl<'de> Deserialize<'de> for MockDatabase {
         for (k, v) in helper.current_skill {
             db.current_skill.insert(k, v);
         }
+        for (k, v) in helper.trades {
+            db.trades.insert(k, v);
+        }
 
         Ok(db)
     }
@@ -69,7 +76,7 @@ impl Serialize for MockDatabase {
     where
         S: Serializer,
     {
-        let mut state = serializer.serialize_struct("MockDatabase", 6)?;
+        let mut state = serializer.serialize_struct("MockDatabase", 7)?;
 
         let character_entries: Vec<(u64, Character)> = self
             .characters
@@ -101,6 +108,11 @@ impl Serialize for MockDatabase {
             .iter()
             .map(|e| (*e.key(), e.value().clone()))
             .collect();
+        let trade_entries: Vec<(u64, Trade)> = self
+            .trades
+            .iter()
+            .map(|e| (*e.key(), e.value().clone()))
+            .collect();
 
         state.serialize_field("characters", &character_entries)?;
         state.serialize_field("enemy", &enemy_entries)?;
@@ -108,6 +120,7 @@ impl Serialize for MockDatabase {
         state.serialize_field("skills", &skill_entries)?;
         state.serialize_field("items", &item_entries)?;
         state.serialize_field("current_skill", &current_skill_entries)?;
+        state.serialize_field("trades", &trade_entries)?;
 
         state.end()
     }
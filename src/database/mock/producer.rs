@@ -3,7 +3,7 @@ use async_trait::async_trait;
 use tracing::instrument;
 
 use crate::database::Producer;
-use crate::database::{CarrionResult, Character, Items, Mob, SkillSet};
+use crate::database::{CarrionResult, Character, Items, Mob, Skill, SkillSet, Trade};
 
 use super::MockDatabase;
 
@@ -53,6 +53,15 @@ impl Producer for MockProducer {
         Ok(())
     }
 
+    #[instrument(skip(self), target = "database_producer")]
+    async fn unlock_skill(&self, skill: Skill, user_id: u64) -> CarrionResult<()> {
+        MockDatabase::get()
+            .skills
+            .entry((user_id, skill as u64))
+            .or_insert_with(|| SkillSet::new(skill));
+        Ok(())
+    }
+
     #[instrument(skip(self, character),  target = "database_producer", fields(user_id = %character.user_id))]
     async fn store_mob_queue(&self, character: &Character, enemies: Vec<Mob>) -> CarrionResult<()> {
         MockDatabase::get().mobs.insert(character.user_id, enemies);
@@ -82,4 +91,16 @@ impl Producer for MockProducer {
         MockDatabase::get().items.insert(user_id, Items::default());
         Ok(())
     }
+
+    #[instrument(skip(self, content),  target = "database_producer", fields(user_id = %content.from))]
+    async fn store_trade(&self, content: Trade) -> CarrionResult<()> {
+        MockDatabase::get().trades.insert(content.from, content);
+        Ok(())
+    }
+
+    #[instrument(skip(self), target = "database_producer")]
+    async fn delete_trade(&self, user_id: u64) -> CarrionResult<()> {
+        MockDatabase::get().trades.remove(&user_id);
+        Ok(())
+    }
 }
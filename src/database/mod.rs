@@ -5,8 +5,9 @@ use serenity::async_trait;
 
 use crate::character::Character;
 use crate::enemy::{Enemy, Mob};
+use crate::game::trade::Trade;
 use crate::item::Items;
-use crate::skill::SkillSet;
+use crate::skill::{Skill, SkillSet};
 use crate::CarrionResult;
 
 #[async_trait]
@@ -18,6 +19,8 @@ pub trait Producer {
     async fn create_or_update_skill(&self, content: SkillSet, user_id: u64) -> CarrionResult<()>;
     async fn set_current_skill(&self, content: SkillSet, user_id: u64) -> CarrionResult<()>;
     async fn delete_character_skills(&self, user_id: u64) -> CarrionResult<()>;
+    /// Ascended skills are unlocked by putting their skill set in the skill table.
+    async fn unlock_skill(&self, skill: Skill, user_id: u64) -> CarrionResult<()>;
 
     async fn store_mob_queue(&self, character: &Character, enemies: Vec<Mob>) -> CarrionResult<()>;
     async fn delete_mob_queue(&self, user_id: u64) -> CarrionResult<()>;
@@ -25,6 +28,9 @@ pub trait Producer {
     async fn store_user_items(&self, content: Items, user_id: u64) -> CarrionResult<()>;
     async fn delete_user_items(&self, user_id: u64) -> CarrionResult<()>;
     async fn create_user_items(&self, user_id: u64) -> CarrionResult<()>;
+
+    async fn store_trade(&self, content: Trade) -> CarrionResult<()>;
+    async fn delete_trade(&self, user_id: u64) -> CarrionResult<()>;
 }
 
 #[async_trait]
@@ -40,7 +46,9 @@ pub trait Consumer {
     ) -> CarrionResult<Option<SkillSet>>;
     async fn get_current_skill(&self, user_id: u64) -> CarrionResult<Option<SkillSet>>;
     async fn get_skill_id(&self, user_id: u64, skill_id: u64) -> CarrionResult<Option<SkillSet>>;
+    async fn get_ascended_skills(&self, user_id: u64) -> CarrionResult<Vec<Skill>>;
     async fn get_items(&self, user_id: u64) -> CarrionResult<Option<Items>>;
+    async fn get_trades(&self) -> CarrionResult<Vec<Trade>>;
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -1,12 +1,16 @@
 use crate::character::Character;
-use crate::database::surreal::{CHARACTER_TABLE, DB, ENEMY_TABLE, ITEM_TABLE, MOB_TABLE};
+use crate::database::surreal::{
+    CHARACTER_TABLE, DB, ENEMY_TABLE, ITEM_TABLE, MOB_TABLE, TRADE_TABLE,
+};
 use crate::database::Consumer;
 use crate::enemy::{Enemy, Mob};
+use crate::game::trade::Trade;
 use crate::item::Items;
 use crate::{CarrionResult, MobQueue};
 
-use crate::skill::SkillSet;
+use crate::skill::{Skill, SkillSet};
 use serenity::async_trait;
+use strum::IntoEnumIterator;
 use tracing::{info, instrument, Level};
 
 pub struct SurrealConsumer {}
@@ -72,6 +76,20 @@ impl Consumer for SurrealConsumer {
         Ok(skill)
     }
 
+    #[instrument(skip(self), target = "database_consumer", ret(level = Level::TRACE))]
+    async fn get_ascended_skills(&self, user_id: u64) -> CarrionResult<Vec<Skill>> {
+        let mut unlocked = vec![];
+        for skill in Skill::iter().filter(|skill| skill.is_ascended()) {
+            let key = (format!("{}", user_id), skill as u64);
+            let skill_set: Option<SkillSet> = DB.select(key).await?;
+            if skill_set.is_some() {
+                unlocked.push(skill);
+            }
+        }
+        info_with_span();
+        Ok(unlocked)
+    }
+
     #[instrument(skip(self), target = "database_consumer", ret(level = Level::TRACE))]
     async fn get_items(&self, user_id: u64) -> CarrionResult<Option<Items>> {
         let key = (ITEM_TABLE, user_id);
@@ -79,4 +97,11 @@ impl Consumer for SurrealConsumer {
         info_with_span();
         Ok(items)
     }
+
+    #[instrument(skip(self), target = "database_consumer", ret(level = Level::TRACE))]
+    async fn get_trades(&self) -> CarrionResult<Vec<Trade>> {
+        let trades: Vec<Trade> = DB.select(TRADE_TABLE).await?;
+        info_with_span();
+        Ok(trades)
+    }
 }
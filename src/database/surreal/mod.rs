@@ -22,6 +22,8 @@ pub static ITEM_TABLE: &str = "items";
 pub static SKILL_TABLE: &str = "skills";
 
 pub static COMBAT_TABLE: &str = "combat";
+
+pub static TRADE_TABLE: &str = "trades";
 pub struct SurrealDB {}
 impl SurrealDB {
     pub async fn connect(address: &str) -> CarrionResult<()> {
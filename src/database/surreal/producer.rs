@@ -1,13 +1,14 @@
-use crate::database::surreal::{CHARACTER_TABLE, DB, ITEM_TABLE, MOB_TABLE};
+use crate::database::surreal::{CHARACTER_TABLE, DB, ITEM_TABLE, MOB_TABLE, TRADE_TABLE};
 use crate::database::Producer;
 use crate::enemy::Mob;
 
 use crate::character::Character;
+use crate::game::trade::Trade;
 use crate::{CarrionResult, MobQueue, Record};
 
 use serenity::async_trait;
 
-use crate::skill::SkillSet;
+use crate::skill::{Skill, SkillSet};
 use tracing::{info, instrument, Level};
 
 pub struct SurrealProducer {}
@@ -62,6 +63,18 @@ impl Producer for SurrealProducer {
         Ok(())
     }
 
+    #[instrument(skip(self), target = "database_producer", ret(level = Level::TRACE))]
+    async fn unlock_skill(&self, skill: Skill, user_id: u64) -> CarrionResult<()> {
+        let key = (format!("{}", user_id), skill as u64);
+        // Keep the level and experience of a skill that is already unlocked
+        let unlocked: Option<SkillSet> = DB.select(key.clone()).await?;
+        if unlocked.is_none() {
+            let _skill: Option<Record> = DB.update(key).content(SkillSet::new(skill)).await?;
+        }
+        info_with_span();
+        Ok(())
+    }
+
     #[instrument(skip(self, character), target = "database_producer", fields(user_id = %character.user_id), ret(level = Level::TRACE))]
     async fn store_mob_queue(&self, character: &Character, enemies: Vec<Mob>) -> CarrionResult<()> {
         let _record: Option<Record> = DB
@@ -113,4 +126,21 @@ impl Producer for SurrealProducer {
         info_with_span();
         Ok(())
     }
+
+    #[instrument(skip(self, content), target = "database_producer", fields(user_id = %content.from), ret(level = Level::TRACE))]
+    async fn store_trade(&self, content: Trade) -> CarrionResult<()> {
+        let _record: Option<Record> = DB
+            .update((TRADE_TABLE, content.from))
+            .content(content)
+            .await?;
+        info_with_span();
+        Ok(())
+    }
+
+    #[instrument(skip(self), target = "database_producer", ret(level = Level::TRACE))]
+    async fn delete_trade(&self, user_id: u64) -> CarrionResult<()> {
+        let _record: Option<Record> = DB.delete((TRADE_TABLE, user_id)).await?;
+        info_with_span();
+        Ok(())
+    }
 }
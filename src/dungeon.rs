@@ -0,0 +1,161 @@
+use std::fmt::Display;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::constructed::ItemsWeHave;
+use crate::enemy::{Enemy, Mob, MobGrade};
+use crate::item::{Equipment, IndividualItem, Items};
+use crate::zone::Zone;
+use crate::{CarrionError, CarrionResult};
+
+pub const ENTRY_FEE: u64 = 10_000;
+pub const MIN_LEVEL: u32 = 10;
+/// Mobs in a run, the boss included.
+pub const MIN_MOBS: usize = 10;
+pub const MAX_MOBS: usize = 20;
+
+/// A dungeon run in progress. The mobs are rolled when the character enters and always end
+/// with a boss. They drop nothing along the way, the gold and a Legendary or rarer item are
+/// only paid out once the boss is dead. Dying or abandoning the run forfeits the entry fee.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct DungeonRun {
+    pub level: u32,
+    pub mobs: Vec<Mob>,
+    pub cleared: usize,
+}
+
+impl DungeonRun {
+    /// Takes the entry fee out of `items` and rolls the mobs of the run.
+    pub fn enter(level: u32, items: &mut Items, rng: &mut impl Rng) -> CarrionResult<Self> {
+        if level < MIN_LEVEL {
+            return Err(CarrionError::DungeonError(format!(
+                "Dungeons need level {}",
+                MIN_LEVEL
+            )));
+        }
+        if items.gold < ENTRY_FEE {
+            return Err(CarrionError::DungeonError(format!(
+                "{} gold is needed to enter, you have {}",
+                ENTRY_FEE, items.gold
+            )));
+        }
+        items.gold -= ENTRY_FEE;
+
+        let length = rng.gen_range(MIN_MOBS..=MAX_MOBS);
+        let mut mobs: Vec<Mob> = (1..length).map(|_| rng.gen()).collect();
+        mobs.push(Mob::from_grade(MobGrade::Boss, rng));
+        Ok(Self {
+            level,
+            mobs,
+            cleared: 0,
+        })
+    }
+
+    pub fn boss(&self) -> Mob {
+        *self.mobs.last().expect("Dungeon run has no boss")
+    }
+
+    pub fn current(&self) -> Option<Mob> {
+        self.mobs.get(self.cleared).copied()
+    }
+
+    pub fn is_cleared(&self) -> bool {
+        self.cleared >= self.mobs.len()
+    }
+
+    /// The mob the character is fighting, stripped of its gold and items.
+    pub fn enemy(&self, rng: &mut impl Rng) -> Option<Enemy> {
+        let mut enemy = self
+            .current()?
+            .generate_in(Zone::default(), self.level, rng);
+        enemy.items.clear();
+        enemy.gold = 0;
+        Some(enemy)
+    }
+
+    /// Counts the current mob as killed, returns whether that cleared the run.
+    pub fn advance(&mut self) -> bool {
+        self.cleared += 1;
+        self.is_cleared()
+    }
+
+    /// Pays out a cleared run, twice the entry fee and a dungeon item the character does not
+    /// have yet, rarer items less likely. Once every item is owned only the gold is left.
+    pub fn loot(
+        &self,
+        items: &mut Items,
+        equipment: &Equipment,
+        rng: &mut impl Rng,
+    ) -> Option<IndividualItem> {
+        items.gold += ENTRY_FEE * 2;
+        let equipped = equipment.items();
+        let candidates: Vec<IndividualItem> = ItemsWeHave::dungeon_loot()
+            .iter()
+            .map(|item| item.generate())
+            .filter(|item| !equipped.contains(item) && items.iter().all(|owned| owned != item))
+            .collect();
+        let item = candidates
+            .choose_weighted(rng, |item| item.rarity as u64)
+            .ok()?
+            .clone();
+        items.stash(item.clone());
+        Some(item)
+    }
+}
+
+impl Display for DungeonRun {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Dungeon level {}: {}/{} cleared",
+            self.level,
+            self.cleared,
+            self.mobs.len()
+        )?;
+        if let Some(mob) = self.current() {
+            write!(f, ", fighting {}", mob)?;
+        }
+        write!(f, ", boss {}", self.boss())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{DungeonRun, ENTRY_FEE, MAX_MOBS, MIN_LEVEL, MIN_MOBS};
+    use crate::{
+        enemy::{Mob, MobGrade},
+        item::{Equipment, Items},
+        rng::CarrionRng,
+        EnemyEvents,
+    };
+    use rand::Rng;
+
+    #[test]
+    fn runs_only_pay_out_once_cleared() {
+        let mut rng = CarrionRng::new(12);
+        let mut items = Items::default();
+        assert!(DungeonRun::enter(MIN_LEVEL, &mut items, &mut rng).is_err());
+        items.gold = ENTRY_FEE;
+        assert!(DungeonRun::enter(MIN_LEVEL - 1, &mut items, &mut rng).is_err());
+
+        let mut run = DungeonRun::enter(MIN_LEVEL, &mut items, &mut rng).unwrap();
+        assert_eq!(items.gold, 0);
+        assert!((MIN_MOBS..=MAX_MOBS).contains(&run.mobs.len()));
+        assert_eq!(run.boss().grade(), MobGrade::Boss);
+        assert!((0..1000).all(|_| rng.gen::<Mob>().grade() != MobGrade::Boss));
+
+        let enemy = run.enemy(&mut rng).unwrap();
+        assert_eq!((enemy.gold, enemy.items.len()), (0, 0));
+        while !run.advance() {}
+        assert!(run.enemy(&mut rng).is_none());
+
+        let item = run
+            .loot(&mut items, &Equipment::default(), &mut rng)
+            .unwrap();
+        assert!(item.rarity <= crate::item::Rarity::Legendary);
+        assert_eq!(items.gold, ENTRY_FEE * 2);
+        assert_eq!(items.iter().count(), 1);
+    }
+}
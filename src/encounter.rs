@@ -0,0 +1,162 @@
+use eris_macro::{ErisDisplayEmoji, ErisValidEnum};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use strum::EnumIter;
+
+use crate::enemy::{Enemy, Mob};
+use crate::skill::Skill;
+use crate::zone::Zone;
+use crate::EnemyEvents;
+
+/// Most enemies fought at once.
+pub const MAX_ENCOUNTER: usize = 3;
+
+/// Which living enemy of an encounter a single target skill hits. Area skills ignore it and
+/// hit all of them.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    Deserialize,
+    ErisValidEnum,
+    ErisDisplayEmoji,
+    EnumIter,
+)]
+pub enum Targeting {
+    /// The first enemy in the group until it is dead.
+    #[default]
+    #[emoji("👉")]
+    First,
+    /// The enemy with the least health left.
+    #[emoji("🩸")]
+    Weakest,
+    /// The enemy with the most health left.
+    #[emoji("💪")]
+    Strongest,
+    /// The first enemy vulnerable to the skill's element, otherwise the first one.
+    #[emoji("🎯")]
+    Vulnerable,
+}
+
+impl Targeting {
+    /// Index of the enemy `skill` is aimed at, `None` once every enemy is dead.
+    pub fn pick(&self, skill: Skill, enemies: &[Enemy]) -> Option<usize> {
+        let mut alive = enemies
+            .iter()
+            .enumerate()
+            .filter(|(_, enemy)| enemy.alive());
+        let target = match self {
+            Targeting::First => alive.next(),
+            Targeting::Weakest => alive.min_by_key(|(_, enemy)| enemy.health),
+            Targeting::Strongest => alive.max_by_key(|(_, enemy)| enemy.health),
+            Targeting::Vulnerable => {
+                let alive: Vec<_> = alive.collect();
+                alive
+                    .iter()
+                    .find(|(_, enemy)| vulnerable(skill, enemy))
+                    .or(alive.first())
+                    .copied()
+            }
+        };
+        target.map(|(index, _)| index)
+    }
+}
+
+pub fn vulnerable(skill: Skill, enemy: &Enemy) -> bool {
+    skill.element().is_some() && skill.element() == enemy.kind.vulnerability()
+}
+
+/// Takes up to `MAX_ENCOUNTER` queued mobs off the front of `queue` to fight at once, or a
+/// single mob from the zone when nothing is queued.
+pub fn encounter(queue: &mut Vec<Mob>, zone: Zone, level: u32, rng: &mut impl Rng) -> Vec<Enemy> {
+    if queue.is_empty() {
+        return vec![zone.enemy(level, rng)];
+    }
+    let count = queue.len().min(MAX_ENCOUNTER);
+    queue
+        .drain(..count)
+        .map(|mob| {
+            let mut enemy = mob.generate_in(zone, level, rng);
+            enemy.queued = true;
+            enemy
+        })
+        .collect()
+}
+
+/// The enemies of an encounter by name, like `Orc, Goblin and Elf`.
+pub fn describe(enemies: &[Enemy]) -> String {
+    let names: Vec<String> = enemies.iter().map(|enemy| enemy.kind.to_string()).collect();
+    match names.split_last() {
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => format!("{} and {}", rest.join(", "), last),
+        None => String::new(),
+    }
+}
+
+/// How a single enemy of an encounter fared in a battle.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnemyReport {
+    pub name: String,
+    pub level: u32,
+    pub hp: i32,
+    pub damage_taken: i32,
+    pub healing: i32,
+    pub killed: bool,
+    /// Whether the enemy used its one-time opening move, kept across battles.
+    #[serde(default)]
+    pub opened: bool,
+}
+
+impl From<&Enemy> for EnemyReport {
+    fn from(enemy: &Enemy) -> Self {
+        Self {
+            name: enemy.kind.to_string(),
+            level: enemy.level,
+            hp: enemy.health,
+            damage_taken: 0,
+            healing: 0,
+            killed: !enemy.alive(),
+            opened: enemy.opened,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{describe, encounter, Targeting, MAX_ENCOUNTER};
+    use crate::{enemy::Mob, rng::CarrionRng, skill::Skill, zone::Zone};
+
+    #[test]
+    fn queued_mobs_are_fought_together() {
+        let mut rng = CarrionRng::new(19);
+        let mut queue = vec![Mob::Orc, Mob::Goblin, Mob::Elf, Mob::Troll];
+        let mut enemies = encounter(&mut queue, Zone::Wilds, 5, &mut rng);
+        assert_eq!(enemies.len(), MAX_ENCOUNTER);
+        assert_eq!(queue, vec![Mob::Troll]);
+        assert!(enemies.iter().all(|enemy| enemy.queued));
+        assert_eq!(
+            describe(&enemies),
+            format!("{}, {} and {}", Mob::Orc, Mob::Goblin, Mob::Elf)
+        );
+        assert_eq!(encounter(&mut queue, Zone::Wilds, 5, &mut rng).len(), 1);
+        let zone_enemies = encounter(&mut queue, Zone::Wilds, 5, &mut rng);
+        assert_eq!(zone_enemies.len(), 1);
+        assert!(!zone_enemies[0].queued);
+
+        enemies[0].health = 0;
+        enemies[1].health = 1;
+        enemies[2].health = 50;
+        assert_eq!(Targeting::First.pick(Skill::Slash, &enemies), Some(1));
+        assert_eq!(Targeting::Weakest.pick(Skill::Slash, &enemies), Some(1));
+        assert_eq!(Targeting::Strongest.pick(Skill::Slash, &enemies), Some(2));
+        // Elves are vulnerable to physical damage
+        assert_eq!(Targeting::Vulnerable.pick(Skill::Slash, &enemies), Some(2));
+        enemies.iter_mut().for_each(|enemy| enemy.health = 0);
+        assert_eq!(Targeting::First.pick(Skill::Slash, &enemies), None);
+    }
+}
@@ -1,16 +1,20 @@
 use std::f64::consts::E;
 
-use crate::damage::Damage;
+use std::collections::HashMap;
+
+use crate::damage::{Damage, DamageType, ResistCategories};
 use crate::item::IndividualItem;
 use crate::skill::MobAction;
+use crate::status::StatusEffects;
 use crate::unit::Attributes;
+use crate::zone::Zone;
 use eris_macro::{ErisDisplayEmoji, ErisFlatMob, ErisValidEnum};
 use rand::seq::IteratorRandom;
 use strum::EnumIter;
 
-use crate::{enemy_defense_scaling, enemy_exp_scaling, sub_linear_scaling};
+use crate::{enemy_defense_scaling, enemy_exp_scaling, sub_linear_scaling, ElementalScaling};
 use rand::prelude::SliceRandom;
-use rand::thread_rng;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -31,7 +35,52 @@ pub struct Enemy {
     pub(crate) attributes: Attributes,
     pub(crate) items: Vec<IndividualItem>,
     pub(crate) state: EnemyState,
+    #[serde(default)]
+    pub(crate) status: StatusEffects,
     actions: Vec<MobAction>,
+    /// Whether the opening move was used.
+    #[serde(default)]
+    pub(crate) opened: bool,
+    /// Whether the player paid for the enemy to be queued, rather than it coming from the zone.
+    #[serde(default)]
+    pub(crate) queued: bool,
+}
+
+/// Health share in percent below which a mob with `Enraged` actions enrages.
+pub const ENRAGE_THRESHOLD: u32 = 25;
+/// Extra damage multiplier of an enraged mob.
+pub const ENRAGE_MULTIPLIER: f64 = 0.5;
+
+/// When a mob can pick an action, declared with `@` in `#[mob(actions = ...)]`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ActionCondition {
+    #[default]
+    Always,
+    /// Only below this share of max health, in percent.
+    HpBelow(u32),
+    /// Only once enraged, enraged mobs use nothing else and hit harder.
+    Enraged,
+    /// Used once as the first action of the fight.
+    Opening,
+}
+
+/// An action of a mob with how often it is picked among the usable ones, declared as
+/// `Action:weight@condition` in `#[mob(actions = ...)]`, like `Regenerate:2@hp<50`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MobActionRule {
+    pub action: MobAction,
+    pub weight: u32,
+    pub condition: ActionCondition,
+}
+
+impl From<MobAction> for MobActionRule {
+    fn from(action: MobAction) -> Self {
+        Self {
+            action,
+            weight: 1,
+            condition: ActionCondition::Always,
+        }
+    }
 }
 impl Enemy {
     fn hp_gain(attributes: &Attributes, level: u32) -> u32 {
@@ -55,11 +104,65 @@ impl Enemy {
         }
     }
 
-    pub fn action(&self) -> (Damage, MobAction) {
-        let mut rng = thread_rng();
-        let action = self.actions.choose(&mut rng).expect("No Skill found");
+    fn health_percent(&self) -> u32 {
+        (self.health.max(0) as u64 * 100 / self.max_health().max(1) as u64) as u32
+    }
+
+    /// Whether the mob is below `ENRAGE_THRESHOLD` and has actions for it.
+    pub fn enraged(&self) -> bool {
+        self.health_percent() < ENRAGE_THRESHOLD
+            && self
+                .rules()
+                .iter()
+                .any(|rule| rule.condition == ActionCondition::Enraged)
+    }
 
-        (action.base_damage(self), action.clone())
+    /// The mob's action rules for the actions this enemy has.
+    fn rules(&self) -> Vec<MobActionRule> {
+        self.kind
+            .action_rules()
+            .into_iter()
+            .filter(|rule| self.actions.contains(&rule.action))
+            .collect()
+    }
+
+    /// Picks an action by the rules of the mob: the opening move first, then weighted among
+    /// the usable actions. Heals are only usable while hurt. Without a usable rule it falls back
+    /// to any of its actions.
+    pub fn action(&mut self, rng: &mut impl Rng) -> (Damage, MobAction) {
+        let rules = self.rules();
+        let enraged = self.enraged();
+        let hurt = self.health < self.max_health() as i32;
+        let opening = rules
+            .iter()
+            .find(|rule| rule.condition == ActionCondition::Opening)
+            .filter(|_| !self.opened);
+        self.opened = true;
+
+        let usable: Vec<&MobActionRule> = rules
+            .iter()
+            .filter(|rule| {
+                hurt || ElementalScaling::scaling(&rule.action) != Some(DamageType::Healing)
+            })
+            .filter(|rule| match rule.condition {
+                ActionCondition::Always => !enraged,
+                ActionCondition::HpBelow(percent) => !enraged && self.health_percent() < percent,
+                ActionCondition::Enraged => enraged,
+                ActionCondition::Opening => false,
+            })
+            .collect();
+
+        let action = match (opening, usable.choose_weighted(rng, |rule| rule.weight)) {
+            (Some(rule), _) => rule.action.clone(),
+            (None, Ok(rule)) => rule.action.clone(),
+            (None, Err(_)) => self.actions.choose(rng).expect("No Skill found").clone(),
+        };
+
+        let mut damage = action.base_damage(self, rng);
+        if enraged {
+            damage.multiplier += ENRAGE_MULTIPLIER;
+        }
+        (damage, action)
     }
 
     pub fn set_actions(mut self, actions: Vec<MobAction>) -> Enemy {
@@ -90,7 +193,10 @@ impl Default for Enemy {
             attributes: Default::default(),
             items: vec![],
             state: EnemyState::Alive,
+            status: StatusEffects::default(),
             actions: vec![],
+            opened: false,
+            queued: false,
         }
     }
 }
@@ -107,22 +213,29 @@ pub enum MobGrade {
 }
 
 impl MobGrade {
-    pub fn to_enemy(&self, mob: Mob, level: u32) -> Enemy {
+    /// `level` is the character's, the zone shifts it and scales the rewards.
+    pub fn to_enemy(&self, mob: Mob, level: u32, zone: Zone, rng: &mut impl Rng) -> Enemy {
         let attributes: Attributes = (&mob).into();
+        let level = zone.level(level);
+        let experience = enemy_exp_scaling(level) as f64 * zone.experience_multiplier();
+        let gold = sub_linear_scaling(level * mob.grade() as u32) as f64 * zone.gold_multiplier();
         let mut enemy = Enemy {
             kind: mob,
             level,
-            experience: enemy_exp_scaling(level),
+            experience: experience as u64,
             health: Enemy::hp_gain(&attributes, level) as i32,
             defense: enemy_defense_scaling(level, mob.grade() as u32) as i32,
             resistance: enemy_defense_scaling(level, mob.grade() as u32) as i32,
-            gold: sub_linear_scaling(level * mob.grade() as u32) as u64,
+            gold: gold as u64,
             items: vec![],
             state: EnemyState::Alive,
+            status: StatusEffects::default(),
             actions: mob.actions(),
+            opened: false,
+            queued: false,
             attributes,
         };
-        enemy.items = (&enemy).into();
+        enemy.items = crate::items::drops(&enemy, rng);
         enemy
     }
 }
@@ -197,54 +310,97 @@ pub enum Mob {
     #[mob(grade = "Strong", alignment = "LawfulEvil", actions = "Explode")]
     Lich,
     #[emoji("🧟")]
-    #[mob(grade = "Strong", alignment = "ChaoticEvil", actions = "Explode")]
+    #[mob(
+        grade = "Strong",
+        alignment = "ChaoticEvil",
+        actions = "Explode:2, Smash:2, Regenerate@hp<50"
+    )]
     Troll,
     #[emoji("🏆")]
-    #[mob(grade = "Champion", alignment = "LawfulNeutral", actions = "Crush")]
+    #[mob(
+        grade = "Champion",
+        alignment = "LawfulNeutral",
+        actions = "Riposte@opening, Crush:3, Riposte"
+    )]
     Gladiator,
     #[emoji("🐘")]
     #[mob(
         grade = "Elite",
         alignment = "ChaoticNeutral",
-        actions = "ShadowNova, SolarFlare"
+        actions = "ShadowNova:2, SolarFlare:2, Smash@enraged"
     )]
     Behemoth,
     #[emoji("🦖")]
     #[mob(
         grade = "Elite",
         alignment = "ChaoticNeutral",
-        actions = "BoneShatter, FrostBreath"
+        actions = "FrostBreath@opening, BoneShatter:3, FrostBreath:2, TailSwipe@enraged"
     )]
     Dreadmaw,
     #[emoji("🐉")]
     #[mob(
         grade = "Legendary",
         alignment = "ChaoticEvil",
-        actions = "DragonBreath, TailSwipe, FieryRoar"
+        actions = "FieryRoar@opening, DragonBreath:3, TailSwipe:3, FieryRoar, Regenerate@hp<40, DragonBreath@enraged"
     )]
     Eldragor,
+    #[emoji("😈")]
+    #[mob(
+        grade = "Boss",
+        alignment = "ChaoticEvil",
+        vulnerability = "Elemental",
+        actions = "ShadowNova@opening, ShadowNova:2, DragonBreath:2, Crush:3, Regenerate@hp<30, NecroticBlast@enraged"
+    )]
+    Archfiend,
+    #[emoji("🪦")]
+    #[mob(
+        grade = "Boss",
+        alignment = "NeutralEvil",
+        vulnerability = "Physical",
+        actions = "SummonUndead@opening, MindBreak:2, BoneShatter:3, FrostBreath:2, NecroticBlast@enraged"
+    )]
+    BoneKing,
 }
 
 impl Mob {
-    pub fn generate(&self, level: u32) -> Enemy {
-        let enemy: Enemy = self.grade().to_enemy(*self, level);
+    pub fn generate(&self, level: u32, rng: &mut impl Rng) -> Enemy {
+        self.generate_in(Zone::default(), level, rng)
+    }
+
+    pub fn generate_in(&self, zone: Zone, level: u32, rng: &mut impl Rng) -> Enemy {
+        let enemy: Enemy = self.grade().to_enemy(*self, level, zone, rng);
         enemy
     }
-}
 
-impl From<MobGrade> for Mob {
-    fn from(value: MobGrade) -> Self {
+    /// Resistances of the mob for an enemy with `resistance`. Every mob has it against
+    /// everything and half again against the damage its own actions deal, unless it is
+    /// vulnerable to that.
+    pub fn resistances(&self, resistance: i32) -> HashMap<ResistCategories, i32> {
+        let mut resistances = ResistCategories::resist_category_hash_map();
+        resistances.insert(ResistCategories::Universal, resistance);
+        let vulnerability = self.vulnerability().map(|dtype| dtype.resist_category());
+        for action in self.actions() {
+            let category = ElementalScaling::scaling(&action)
+                .unwrap_or_default()
+                .resist_category();
+            if category != ResistCategories::Universal && Some(category) != vulnerability {
+                resistances.insert(category, resistance / 2);
+            }
+        }
+        resistances
+    }
+
+    pub fn from_grade(grade: MobGrade, rng: &mut impl Rng) -> Mob {
         use strum::IntoEnumIterator;
-        let matches = Mob::iter().filter(|m| m.grade() == value);
-        let mut rng = thread_rng();
-        matches.choose(&mut rng).unwrap()
+        let matches = Mob::iter().filter(|m| m.grade() == grade);
+        matches.choose(rng).unwrap()
     }
 }
 
 #[cfg(test)]
 mod test {
 
-    use crate::{character::Character, unit::Alignment};
+    use crate::{character::Character, rng::CarrionRng, unit::Alignment};
 
     use super::*;
 
@@ -281,7 +437,7 @@ mod test {
     fn sane_exp_gains() {
         let mut character = Character::new("sd".to_string(), 1, crate::class::Classes::Wizard);
         character.level = 20;
-        let enemy = Mob::Orc.generate(character.level);
+        let enemy = Mob::Orc.generate(character.level, &mut CarrionRng::new(9));
         let nxt = character.experience_to_next_level();
         assert!(
             enemy.experience < nxt / 5,
@@ -290,4 +446,37 @@ mod test {
             nxt
         );
     }
+
+    #[test]
+    fn mobs_follow_their_action_rules() {
+        let mut rng = CarrionRng::new(18);
+        let rules = Mob::Troll.action_rules();
+        assert_eq!(rules[0].weight, 2);
+        assert_eq!(rules[2].condition, ActionCondition::HpBelow(50));
+
+        let mut eldragor = Mob::Eldragor.generate(30, &mut rng);
+        assert_eq!(eldragor.action(&mut rng).1, MobAction::FieryRoar);
+        for _ in 0..100 {
+            assert_ne!(eldragor.action(&mut rng).1, MobAction::Regenerate);
+        }
+
+        eldragor.health = eldragor.max_health() as i32 / 3;
+        assert!((0..100).any(|_| eldragor.action(&mut rng).1 == MobAction::Regenerate));
+
+        eldragor.health = eldragor.max_health() as i32 / 5;
+        assert!(eldragor.enraged());
+        for _ in 0..20 {
+            let (damage, action) = eldragor.action(&mut rng);
+            assert_eq!(action, MobAction::DragonBreath);
+            assert!(damage.multiplier > 1.0);
+        }
+    }
+
+    #[test]
+    fn openers_are_reported_for_the_next_battle() {
+        let character = Character::new("Opener".into(), 1, crate::class::Classes::Warrior);
+        let eldragor = Mob::Eldragor.generate(30, &mut CarrionRng::new(21));
+        let (battle_info, _) = crate::replay::BattleLog::synchro(&character, &[eldragor], 21);
+        assert!(battle_info.enemies[0].opened);
+    }
 }
@@ -26,7 +26,7 @@ impl Buffer {
                 dashmap::try_result::TryResult::Present(r) => {
                     let mut mutations = r.value().clone();
                     mutations.extend(mutation.clone());
-                    self.mutations.insert(key, mutation.clone());
+                    self.mutations.insert(key, mutations);
                     m = true;
                 }
 
@@ -81,6 +81,13 @@ impl Buffer {
         self.mutations.iter()
     }
 
+    /// Removes and returns the pending mutations so each one is applied once.
+    pub fn take(&self, character: u64) -> Option<Vec<Mutations>> {
+        self.mutations
+            .remove(&character)
+            .map(|(_, mutations)| mutations)
+    }
+
     pub fn clear(&self, character: u64) {
         self.mutations.remove(&character);
     }
@@ -1,12 +1,17 @@
-use crate::character::Character;
+use crate::character::{Character, DEATH_PENALTY};
 use crate::database::Database;
+use crate::dungeon::DungeonRun;
+use crate::encounter::encounter;
 use crate::enemy::{Enemy, Mob};
 use crate::game::mutations::Mutations;
 
 use crate::item::{IndividualItem, Items};
-use crate::skill::SkillSet;
-use rand::random;
-use std::collections::HashSet;
+use crate::shop;
+use crate::skill::{Skill, SkillSet, MASTERY_LEVEL};
+use crate::BattleInfo;
+use rand::Rng;
+use std::collections::{HashMap, HashSet};
+use strum::IntoEnumIterator;
 
 use tracing::{info, trace, warn};
 
@@ -16,7 +21,8 @@ pub struct CharacterData {
     pub enemies: Vec<Mob>,
     pub items: Items,
     pub user_id: u64,
-    pub active_enemy: Option<Enemy>,
+    /// The encounter being fought, one or more enemies at once.
+    pub active_enemies: Vec<Enemy>,
     pub database: Database,
 }
 
@@ -37,52 +43,55 @@ impl CharacterData {
             .await
             .expect("Unable to get active enemy from DB");
 
+        let mut character = character.clone();
+        character.ascended = consumer
+            .get_ascended_skills(character.user_id)
+            .await
+            .unwrap_or_default();
+
         trace!("Active Enemy: {:?}", active_enemy);
         trace!("Enemies: {:?}", enemies);
         trace!("Items: {:?}", items);
         CharacterData {
-            character: character.clone(),
+            user_id: character.user_id,
+            character,
             enemies,
             items: items.unwrap_or(Items::default()),
-            user_id: character.user_id,
-            active_enemy,
+            active_enemies: active_enemy.into_iter().collect(),
             database,
         }
     }
 
-    pub async fn apply_mutation(&mut self, mutation: Mutations) {
+    pub async fn apply_mutation(&mut self, mutation: Mutations, rng: &mut (impl Rng + Send)) {
         match mutation {
             Mutations::Skill(_, skill) => {
-                let _ = self
-                    .database
-                    .get_producer()
-                    .create_or_update_skill(
-                        self.character.current_skill.clone(),
-                        self.character.user_id,
-                    )
-                    .await
-                    .map_err(|e| {
-                        warn!("Failed to update skill: {:?}", e);
-                    });
-                if let Ok(Some(known_skill)) = self
-                    .database
-                    .get_consumer()
-                    .get_skill(&self.character, skill as u64)
+                if skill.is_ascended() && !self.character.ascended.contains(&skill) {
+                    warn!("Skill {} has not been unlocked yet", skill);
+                    return;
+                }
+                self.save_skills().await;
+                let skill_set = self
+                    .known_skill(skill)
                     .await
-                {
-                    self.character.current_skill = known_skill;
-                } else {
-                    self.character.current_skill = SkillSet::new(skill);
+                    .unwrap_or_else(|| SkillSet::new(skill));
+                self.character.use_skill(skill_set);
+                self.set_current_skill().await;
+            }
+
+            Mutations::Rotation(_, rotation) => {
+                self.save_skills().await;
+                let mut known = vec![];
+                for skill in rotation.skills() {
+                    known.extend(self.known_skill(skill).await);
                 }
+                match self.character.set_rotation(rotation, &known) {
+                    Ok(()) => self.set_current_skill().await,
+                    Err(e) => warn!("Unable to set rotation: {}", e),
+                }
+            }
 
-                let _ = self
-                    .database
-                    .get_producer()
-                    .set_current_skill(self.character.current_skill.clone(), self.character.user_id)
-                    .await
-                    .map_err(|e| {
-                        warn!("Failed to set current skill: {:?}", e);
-                    });
+            Mutations::Targeting(_, targeting) => {
+                self.character.targeting = targeting;
             }
 
             Mutations::Equip(_user_id, item) => {
@@ -105,15 +114,26 @@ impl CharacterData {
 
                 if new_trait {
                     info!("Trait {} inserted", trait_);
-                    self.character.available_traits -= 1;
                 }
                 info!("Available traits: {}", self.character.available_traits);
                 info!("Traits: {:?}", self.character.get_traits());
             }
 
+            Mutations::BoostAttribute(_user_id, attribute) => {
+                match self.character.boost_attribute(&attribute) {
+                    Ok(boost) => info!("{} gained {} {}", self.character.name, boost, attribute),
+                    Err(e) => warn!("Unable to boost attribute: {}", e),
+                }
+            }
+
+            Mutations::Respec(_user_id) => match self.character.respec(&mut self.items) {
+                Ok(refund) => info!("{} respecced {} trait points", self.character.name, refund),
+                Err(e) => warn!("Unable to respec: {}", e),
+            },
+
             Mutations::AddEnemy(_user_id, mob, count) => {
                 for _ in 0..count {
-                    let cost = mob.generate(self.character.level).cost();
+                    let cost = mob.generate(self.character.level, rng).cost();
                     self.items.gold = self.items.gold.saturating_sub(cost);
                     if self.items.gold < cost {
                         self.items.gold += cost;
@@ -124,8 +144,14 @@ impl CharacterData {
             }
 
             Mutations::Sell(_user_id, slot, known_items) => {
-                self.items
-                    .sell_with_knowledge(slot.as_ref(), known_items.as_ref());
+                let gold = self.items.gold;
+                self.items.sell_with_knowledge(
+                    slot.as_ref(),
+                    known_items.as_ref(),
+                    &self.character.loadouts.items(),
+                );
+                let earned = self.items.gold - gold;
+                self.items.gold = gold + self.character.mutations().sell_price(earned);
             }
 
             Mutations::NewItems(_user_id, items) => {
@@ -134,23 +160,30 @@ impl CharacterData {
                     .filter_map(|item| self.character.equipment.auto_equip(item.clone()))
                     .collect();
 
-                let return_items = self
-                    .character
-                    .equipment
-                    .boost(Items::new(unset_items, 0), self.character.clone());
+                let return_items = self.character.equipment.boost(
+                    Items::new(unset_items, 0),
+                    self.character.clone(),
+                    rng,
+                );
 
                 self.items += Items::new(return_items, items.gold);
             }
 
             Mutations::UpdateEnemies(_user_id, battle_info) => {
-                let enemy = self.active_enemy.as_mut().unwrap();
-                enemy.health -= battle_info.player_damage;
-                if battle_info.enemy_healing > 0 {
-                    let max_heal = enemy.health / 4;
-                    let heal = battle_info.enemy_healing.min(max_heal);
-                    enemy.health += heal;
+                for (enemy, report) in self.active_enemies.iter_mut().zip(&battle_info.enemies) {
+                    enemy.health = report.hp;
+                    enemy.opened = report.opened;
+                }
+
+                if self.character.dungeon.is_some() {
+                    if battle_info.player_killed {
+                        info!("{} failed their dungeon run", self.character.name);
+                        self.leave_dungeon(rng);
+                    } else if battle_info.enemy_killed {
+                        self.advance_dungeon(rng);
+                    }
+                    return;
                 }
-                enemy.health = enemy.health.min(enemy.max_health() as i32);
 
                 let enemy_level = if battle_info.enemy_damage == 0 {
                     battle_info.enemy_level + 3
@@ -163,30 +196,25 @@ impl CharacterData {
                 );
 
                 if battle_info.enemy_killed {
-                    if self.enemies.is_empty() {
-                        let mob: Mob = random();
-                        let enemy = mob.generate(enemy_level);
-                        self.active_enemy = Some(enemy.clone());
-                        return;
-                    }
-                    let enemy = self.enemies.remove(0);
-
-                    let enemy = enemy.generate(enemy_level);
-                    self.active_enemy = Some(enemy.clone());
+                    self.active_enemies =
+                        encounter(&mut self.enemies, self.character.zone, enemy_level, rng);
                 }
             }
 
             Mutations::UpdatePlayer(_user_id, battle_info) => {
                 self.character.hp -= battle_info.enemy_damage;
                 self.character.hp += battle_info.player_healing;
+                self.character.hp = self.character.hp.clamp(0, self.character.max_hp as i32);
 
-                // Heal after battle
-                if self.character.hp <= 0 {
-                    self.character.hp = self.character.max_hp as i32;
+                if battle_info.player_killed || self.character.hp == 0 {
+                    let penalty = (self.items.gold as f64 * DEATH_PENALTY) as u64;
+                    self.items.gold -= penalty;
+                    self.character.die();
+                    info!(
+                        "{} died, lost {} gold and is respawning",
+                        self.character.name, penalty
+                    );
                     return;
-                } else {
-                    self.character.hp += (self.character.max_hp / 4) as i32;
-                    self.character.hp = self.character.hp.min(self.character.max_hp as i32);
                 }
 
                 self.character.experience += battle_info.experience_gained;
@@ -195,11 +223,213 @@ impl CharacterData {
             }
 
             Mutations::UpdateSkills(_user_id, battle_info) => {
-                self.character.current_skill.experience += battle_info.skill_experience_gained;
-                self.character.current_skill.try_level_up();
+                self.character.gain_skill_experience(&battle_info);
+                for skill in battle_info.discovered {
+                    if self.character.ascended.contains(&skill) {
+                        continue;
+                    }
+                    info!("Unlocked ascended skill {}", skill);
+                    self.character.ascended.push(skill);
+                    let _ = self
+                        .database
+                        .get_producer()
+                        .unlock_skill(skill, self.user_id)
+                        .await
+                        .map_err(|e| warn!("Failed to unlock skill: {:?}", e));
+                }
+            }
+
+            Mutations::Rest(_user_id) => {
+                self.character.start_rest();
             }
 
+            Mutations::Recover(_user_id) => {
+                self.character.recover();
+            }
+
+            Mutations::SaveLoadout(_user_id, name) => {
+                self.character
+                    .loadouts
+                    .save(name, &self.character.equipment);
+            }
+
+            Mutations::SwapLoadout(_user_id, name) => {
+                let missing = self.character.loadouts.swap(
+                    &name,
+                    &mut self.character.equipment,
+                    &mut self.items,
+                );
+                match missing {
+                    Some(missing) if !missing.is_empty() => {
+                        info!("Loadout {} is missing {} items", name, missing.len())
+                    }
+                    Some(_) => {}
+                    None => warn!("No loadout named {}", name),
+                }
+            }
+
+            Mutations::DeleteLoadout(_user_id, name) => {
+                self.character.loadouts.remove(&name);
+            }
+
+            Mutations::Zone(_user_id, zone) => {
+                if self.character.dungeon.is_some() {
+                    warn!("{} is in a dungeon", self.character.name);
+                    return;
+                }
+                if !zone.can_enter(self.character.level) {
+                    warn!("{} is too low level for {}", self.character.name, zone);
+                    return;
+                }
+                self.character.zone = zone;
+                self.requeue_enemies();
+                self.active_enemies = vec![zone.enemy(self.character.level, rng)];
+            }
+
+            Mutations::EnterDungeon(_user_id) => {
+                if self.character.dungeon.is_some() {
+                    warn!("{} is already in a dungeon", self.character.name);
+                    return;
+                }
+                match DungeonRun::enter(self.character.level, &mut self.items, rng) {
+                    Ok(run) => {
+                        self.requeue_enemies();
+                        self.active_enemies = run.enemy(rng).into_iter().collect();
+                        info!("{} entered a dungeon, {}", self.character.name, run);
+                        self.character.dungeon = Some(run);
+                    }
+                    Err(e) => warn!("Unable to enter dungeon: {}", e),
+                }
+            }
+
+            Mutations::AbandonDungeon(_user_id) if self.character.dungeon.is_some() => {
+                info!("{} abandoned their dungeon run", self.character.name);
+                self.leave_dungeon(rng);
+            }
+
+            Mutations::Promote(_user_id, class) => match self.character.promote(class) {
+                Ok(()) => info!("{} was promoted to {}", self.character.name, class),
+                Err(e) => warn!("Unable to promote: {}", e),
+            },
+
+            Mutations::Multiclass(_user_id, class) => match self.character.multiclass(class) {
+                Ok(()) => info!("{} is now also a {}", self.character.name, class),
+                Err(e) => warn!("Unable to multiclass: {}", e),
+            },
+
+            Mutations::Buy(_user_id, item) => match shop::buy(&mut self.items, item) {
+                Ok(item) => info!("Bought {}", item.name),
+                Err(e) => warn!("Unable to buy item: {}", e),
+            },
+
+            Mutations::Gamble(_user_id) => match shop::gamble(&mut self.items, rng) {
+                Ok(item) => info!("Gambled for {}", item.name),
+                Err(e) => warn!("Unable to gamble: {}", e),
+            },
+
             _ => {}
         }
     }
+
+    /// Stores every skill the character is leveling so switching back keeps the experience.
+    async fn save_skills(&self) {
+        for skill_set in self.character.skill_sets() {
+            let _ = self
+                .database
+                .get_producer()
+                .create_or_update_skill(skill_set, self.character.user_id)
+                .await
+                .map_err(|e| {
+                    warn!("Failed to update skill: {:?}", e);
+                });
+        }
+    }
+
+    /// Ascended skills the skill levels reached after `battle_info` would unlock. The skill
+    /// table is only asked for the other half of a recipe once an equipped skill is mastered.
+    pub async fn discoveries(&self, battle_info: &BattleInfo) -> Vec<Skill> {
+        let mut character = self.character.clone();
+        character.gain_skill_experience(battle_info);
+        let mut levels: HashMap<Skill, u32> = character
+            .skill_sets()
+            .into_iter()
+            .map(|skill_set| (skill_set.skill, skill_set.level))
+            .collect();
+        let mastered = |skill: &Skill| levels.get(skill).is_some_and(|l| *l >= MASTERY_LEVEL);
+        let mut missing = vec![];
+        for (first, second) in Skill::iter()
+            .filter(|skill| !character.ascended.contains(skill))
+            .filter_map(|skill| skill.recipe())
+            .filter(|(first, second)| mastered(first) || mastered(second))
+        {
+            missing.extend(
+                [first, second]
+                    .into_iter()
+                    .filter(|s| !levels.contains_key(s)),
+            );
+        }
+        for skill in missing {
+            if let Some(skill_set) = self.known_skill(skill).await {
+                levels.insert(skill, skill_set.level);
+            }
+        }
+        crate::skill::discover(&levels, &character.ascended)
+    }
+
+    async fn known_skill(&self, skill: Skill) -> Option<SkillSet> {
+        self.database
+            .get_consumer()
+            .get_skill(&self.character, skill as u64)
+            .await
+            .ok()
+            .flatten()
+    }
+
+    async fn set_current_skill(&self) {
+        let _ = self
+            .database
+            .get_producer()
+            .set_current_skill(self.character.current_skill.clone(), self.character.user_id)
+            .await
+            .map_err(|e| {
+                warn!("Failed to set current skill: {:?}", e);
+            });
+    }
+
+    /// Moves the run past the mob that was just killed and pays out the loot after the boss.
+    fn advance_dungeon(&mut self, rng: &mut impl Rng) {
+        let Some(run) = self.character.dungeon.as_mut() else {
+            return;
+        };
+        if !run.advance() {
+            self.active_enemies = run.enemy(rng).into_iter().collect();
+            return;
+        }
+        let loot = run.loot(&mut self.items, &self.character.equipment, rng);
+        info!(
+            "{} cleared a dungeon and looted {:?}",
+            self.character.name,
+            loot.map(|item| item.name)
+        );
+        self.leave_dungeon(rng);
+    }
+
+    /// Ends the run and goes back to hunting in the character's zone.
+    /// Puts the living enemies of the encounter that were bought with `AddEnemy` back at the
+    /// front of the queue, so swapping the encounter out doesn't lose them.
+    fn requeue_enemies(&mut self) {
+        let queued: Vec<Mob> = self
+            .active_enemies
+            .drain(..)
+            .filter(|enemy| enemy.queued && enemy.alive())
+            .map(|enemy| enemy.kind)
+            .collect();
+        self.enemies.splice(0..0, queued);
+    }
+
+    fn leave_dungeon(&mut self, rng: &mut impl Rng) {
+        self.character.dungeon = None;
+        self.requeue_enemies();
+        self.active_enemies = vec![self.character.zone.enemy(self.character.level, rng)];
+    }
 }
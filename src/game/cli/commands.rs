@@ -1,12 +1,21 @@
 use crossterm::style::Color;
 
+use std::path::Path;
+
+use rand::Rng;
+
 use crate::{
     character::Character,
-    class::Classes,
-    item::EquipmentSlot,
+    class::{Classes, MULTICLASS_LEVEL, PROMOTION_LEVEL},
+    encounter::{self, Targeting},
+    item::{EquipmentSlot, ItemComparison},
     r#trait::CharacterTraits,
+    replay::{BattleEvent, BattleKind, BattleLog, BattleRecorder, Combatant},
+    rotation::Rotation,
+    shop::{self, Shop},
     skill::{Skill, SkillSet},
-    ui::cli::{Messages, TICK_RATE},
+    ui::cli::{Messages, TerminalMessages, TICK_RATE},
+    unit::ATTRIBUTES,
     ValidEnum,
 };
 
@@ -31,12 +40,12 @@ pub(crate) fn create(state: &mut GameState, args: &[&str]) -> Messages {
             format!("Invalid class {args:?} choose one of the following"),
             Color::Red,
         );
-        msg.extend(Classes::valid_flat(), Color::Magenta);
+        msg.extend(Classes::starting(), Color::Magenta);
         return msg;
     }
 
     match Classes::try_from(args.first().unwrap().to_string()) {
-        Ok(class) => {
+        Ok(class) if class.is_base() => {
             let mut msg = vec![];
             if let Some(x) = args.get(1) {
                 state.name = Some(x.to_string());
@@ -77,14 +86,13 @@ pub(crate) fn create(state: &mut GameState, args: &[&str]) -> Messages {
             }
             msg.into()
         }
-        Err(_) => {
+        _ => {
             let mut msg = Messages::new();
-            let _valid_classes = Classes::valid_flat();
             msg.push(
                 format!("Invalid class {args:?} choose one of the following"),
                 Color::Red,
             );
-            msg.extend(Classes::valid_flat(), Color::Magenta);
+            msg.extend(Classes::starting(), Color::Magenta);
             msg
         }
     }
@@ -231,19 +239,83 @@ pub(crate) fn select_trait(state: &mut GameState, args: &[&str]) -> Messages {
     msg
 }
 
+pub(crate) fn boost(state: &mut GameState, args: &[&str]) -> Messages {
+    let mut msg = Messages::new();
+    if state.character.is_none() {
+        return msg;
+    }
+    let Some(attribute) = args.first() else {
+        let character = state.get_character_ref_unchecked();
+        msg.push(
+            format!("{} trait points available", character.available_traits),
+            Color::Blue,
+        );
+        for attribute in ATTRIBUTES {
+            msg.push(
+                format!("{}: +{}", attribute, character.attribute_boost(attribute)),
+                Color::Blue,
+            );
+        }
+        return msg;
+    };
+    match state
+        .get_character_mut_unchecked()
+        .boost_attribute(attribute)
+    {
+        Ok(boost) => msg.push(
+            format!("{} increased by {}", attribute, boost),
+            Color::Green,
+        ),
+        Err(err) => msg.push(err.to_string(), Color::Red),
+    }
+    msg
+}
+
+pub(crate) fn respec(state: &mut GameState, _args: &[&str]) -> Messages {
+    let Some(character) = state.character.as_mut() else {
+        return vec![("You have no character".to_string(), Color::Red)].into();
+    };
+    let mut msg = Messages::new();
+    let mut items = character.items.clone();
+    match character.respec(&mut items) {
+        Ok(refund) => msg.push(
+            format!(
+                "Paid {} gold to get {} trait points back",
+                character.respec_cost(),
+                refund
+            ),
+            Color::Green,
+        ),
+        Err(e) => msg.push(e.to_string(), Color::Red),
+    }
+    character.items = items;
+    msg
+}
+
 pub(crate) fn change_skill(state: &mut GameState, args: &[&str]) -> Messages {
     let mut msg = Messages::new();
     if args.is_empty() {
-        let skill_display = state
-            .get_character_ref_unchecked()
-            .current_skill
-            .display_for_cli();
+        let character = state.get_character_ref_unchecked();
+        let skill_display = character.current_skill.display_for_cli();
         msg.extend(skill_display, Color::White);
+        if !character.rotation.is_empty() {
+            msg.push(format!("Rotation: {}", character.rotation), Color::White);
+        }
+    } else if args.len() > 1 || args[0].contains(':') {
+        let character = state.get_character_mut_unchecked();
+        match Rotation::parse(args).and_then(|rotation| character.set_rotation(rotation, &[])) {
+            Ok(()) => msg.push(format!("Rotation: {}", character.rotation), Color::Green),
+            Err(e) => msg.push(e.to_string(), Color::Red),
+        }
     } else {
         match choose_skill(state.character.as_ref().unwrap(), args) {
             Ok((ok_msg, skill)) => {
                 msg.send(ok_msg);
-                state.character.as_mut().unwrap().current_skill = SkillSet::new(skill);
+                state
+                    .character
+                    .as_mut()
+                    .unwrap()
+                    .use_skill(SkillSet::new(skill));
             }
             Err(e) => {
                 msg.send(e);
@@ -314,7 +386,7 @@ pub(crate) fn auto_fight(state: &mut GameState, args: &[&str]) -> Messages {
         .into();
     }
 
-    if state.location.enemy().is_none() {
+    if state.location.enemies().is_empty() {
         return vec![("There is nothing to fight here".to_string(), Color::Red)].into();
     }
 
@@ -331,11 +403,31 @@ pub(crate) fn fight(state: &mut GameState, args: &[&str]) -> Messages {
     let mut msg = Vec::new();
     match state.state.clone() {
         State::Fighting => {
+            if state.battle.is_none() {
+                state.get_character_mut_unchecked().status.clear();
+                state.get_character_mut_unchecked().buffs.clear();
+                let enemies = state.location.get_mut_enemies();
+                enemies.retain(|enemy| enemy.alive());
+                enemies.iter_mut().for_each(|enemy| enemy.status.clear());
+                let seed = state.rng.gen();
+                state.battle = Some(BattleRecorder::new(
+                    BattleKind::Cli,
+                    seed,
+                    state.character.as_ref().unwrap(),
+                    state.location.enemies(),
+                ));
+            }
+            let mut skill_change = None;
             if args.len() == 1 {
                 match choose_skill(state.character.as_ref().unwrap(), args) {
                     Ok((rmsg, skill)) => {
                         msg.extend(rmsg.iter().cloned());
-                        state.character.as_mut().unwrap().current_skill = SkillSet::new(skill);
+                        state
+                            .character
+                            .as_mut()
+                            .unwrap()
+                            .use_skill(SkillSet::new(skill));
+                        skill_change = Some(skill);
                     }
                     Err(msg) => return msg,
                 }
@@ -344,38 +436,56 @@ pub(crate) fn fight(state: &mut GameState, args: &[&str]) -> Messages {
                 <= (state.get_character_ref_unchecked().max_hp as f64 * 0.35) as i32
             {
                 msg.push(("You attempt to flee".to_string(), Color::Red));
+                state.finish_battle();
                 msg.extend(Locations::flee(state));
                 state.state = State::Null;
                 return msg.into();
             }
-            let enemy = state.location.get_mut_enemy();
-
-            let damage_done = state.character.as_mut().unwrap().cli_player(enemy);
-
-            if !enemy.alive() {
-                let mut messages = Messages::new();
+            let enemies = state.location.get_mut_enemies();
+            let recorder = state.battle.as_mut().unwrap();
+            let round = recorder.round(state.character.as_mut().unwrap(), enemies, skill_change);
+            let damage_done = round.damage_by(Combatant::Player);
+            let damage_taken = round.damage_by(Combatant::Enemy);
+            let status_ticks: TerminalMessages = round
+                .events
+                .iter()
+                .filter_map(|event| match event {
+                    BattleEvent::Status(tick) => Some((tick.to_string(), Color::DarkGreen)),
+                    _ => None,
+                })
+                .collect();
+            let battle_info = recorder.battle_info.clone();
+
+            if battle_info.enemy_killed {
+                let mut messages: Messages = status_ticks.into();
                 state.state = State::Null;
 
-                let character_display = state.character.as_ref().unwrap().display_for_cli();
-
-                messages.push(format!("You have killed the {}", &enemy.kind), Color::Green);
                 messages.push(
-                    format!("You have gained {} experience", enemy.experience),
+                    format!("You have killed the {}", battle_info.monster_name),
+                    Color::Green,
+                );
+                messages.push(
+                    format!(
+                        "You have gained {} experience",
+                        battle_info.experience_gained
+                    ),
                     Color::Cyan,
                 );
-                if !enemy.items.is_empty() {
+                if !battle_info.item_gained.is_empty() {
                     messages.push(
-                        format!("You have gained {} gold", enemy.gold),
+                        format!("You have gained {} gold", battle_info.gold_gained),
                         Color::Yellow,
                     );
-                    enemy.items.iter().for_each(|item| {
+                    battle_info.item_gained.iter().for_each(|item| {
                         item.cli_display().into_iter().for_each(|x| {
                             messages.push(x, Color::DarkYellow);
                         });
                     });
                 }
 
-                state.location.enemy_killed();
+                state.location.enemies_killed();
+                state.finish_battle();
+                let character_display = state.get_character_ref_unchecked().display_for_cli();
 
                 messages.push(
                     format!(
@@ -387,10 +497,9 @@ pub(crate) fn fight(state: &mut GameState, args: &[&str]) -> Messages {
                 messages.extend(character_display, Color::White);
                 return messages;
             }
-            let damage_taken = state.character.as_mut().unwrap().cli_enemy(enemy);
 
-            if state.character.as_ref().unwrap().hp <= 0 {
-                let mut msg = Vec::new();
+            if battle_info.player_killed {
+                let mut msg = status_ticks;
                 msg.push((
                     format!(
                         "You did {damage_done} damage with {}",
@@ -408,6 +517,9 @@ pub(crate) fn fight(state: &mut GameState, args: &[&str]) -> Messages {
                     "Create a new character to continue your adventures".to_string(),
                     Color::Blue,
                 ));
+                if let Some(saved) = state.finish_battle() {
+                    msg.push((saved, Color::DarkGrey));
+                }
                 state.character = None;
                 state.location.go_to_origin();
                 state.state = State::Null;
@@ -417,6 +529,7 @@ pub(crate) fn fight(state: &mut GameState, args: &[&str]) -> Messages {
 
             state.state = State::Fighting;
 
+            msg.extend(status_ticks);
             msg.push((
                 format!(
                     "You did {damage_done} damage with {}",
@@ -425,10 +538,29 @@ pub(crate) fn fight(state: &mut GameState, args: &[&str]) -> Messages {
                 Color::Magenta,
             ));
             msg.push((format!("Enemy did {damage_taken} damage",), Color::Blue));
-            msg.push((
-                format!("Enemy {} has {} health remaining", enemy.kind, enemy.health,),
-                Color::DarkBlue,
-            ));
+            for enemy in battle_info.enemies.iter().filter(|e| !e.killed) {
+                msg.push((
+                    format!("Enemy {} has {} health remaining", enemy.name, enemy.hp),
+                    Color::DarkBlue,
+                ));
+            }
+            for enemy in state
+                .location
+                .enemies()
+                .iter()
+                .filter(|e| e.alive() && !e.status.is_empty())
+            {
+                msg.push((
+                    format!("Enemy {} is suffering from {}", enemy.kind, enemy.status),
+                    Color::DarkGreen,
+                ));
+            }
+            if let Some(character) = state.character.as_ref().filter(|c| !c.buffs.is_empty()) {
+                msg.push((
+                    format!("You are buffed by {}", character.buffs),
+                    Color::Green,
+                ));
+            }
             msg.push((
                 format!(
                     "You have {} health remaining",
@@ -440,13 +572,11 @@ pub(crate) fn fight(state: &mut GameState, args: &[&str]) -> Messages {
             msg.into()
         }
         State::Null => {
-            if let Some(mob) = state.location.enemy() {
+            let enemies = state.location.enemies();
+            if !enemies.is_empty() {
+                let group = encounter::describe(enemies);
                 state.state = State::Fighting;
-                vec![(
-                    format!("You are fighting a {mob}", mob = mob.kind),
-                    Color::Red,
-                )]
-                .into()
+                vec![(format!("You are fighting the {group}"), Color::Red)].into()
             } else {
                 vec![("There is nothing to fight here".to_string(), Color::Red)].into()
             }
@@ -455,6 +585,244 @@ pub(crate) fn fight(state: &mut GameState, args: &[&str]) -> Messages {
     }
 }
 
+pub(crate) fn loadout(state: &mut GameState, args: &[&str]) -> Messages {
+    let Some(character) = state.character.as_mut() else {
+        return vec![("You have no character".to_string(), Color::Red)].into();
+    };
+    let mut msg = Messages::new();
+    match args {
+        ["save", name] => {
+            character
+                .loadouts
+                .save(name.to_string(), &character.equipment);
+            msg.push(format!("Saved your equipment as {name}"), Color::Green);
+        }
+        ["delete", name] => match character.loadouts.remove(name) {
+            true => msg.push(format!("Deleted {name}"), Color::Green),
+            false => msg.push(format!("No loadout named {name}"), Color::Red),
+        },
+        [name] => {
+            match character
+                .loadouts
+                .swap(name, &mut character.equipment, &mut character.items)
+            {
+                Some(missing) => {
+                    msg.push(format!("Swapped to {name}"), Color::Green);
+                    for item in missing {
+                        msg.push(format!("{} is no longer yours", item.name), Color::Red);
+                    }
+                    msg.extend(character.equipment.display_for_cli(), Color::Magenta);
+                }
+                None => msg.push(format!("No loadout named {name}"), Color::Red),
+            }
+        }
+        [] if character.loadouts.names().next().is_some() => {
+            let active = character.loadouts.active().cloned();
+            for name in character.loadouts.names() {
+                match Some(name) == active.as_ref() {
+                    true => msg.push(format!("{name} (active)"), Color::Green),
+                    false => msg.push(name.to_string(), Color::Magenta),
+                }
+            }
+        }
+        [] => msg.push("You have no loadouts".to_string(), Color::Red),
+        _ => msg.push(
+            "Usage ( loadout [ optional ( save | delete ) ] [ name ] )".to_string(),
+            Color::Red,
+        ),
+    }
+    msg
+}
+
+pub(crate) fn inspect(state: &mut GameState, args: &[&str]) -> Messages {
+    let Some(character) = state.character.as_ref() else {
+        return vec![("You have no character".to_string(), Color::Red)].into();
+    };
+    if args.is_empty() {
+        return vec![("Usage ( inspect [ item ] )".to_string(), Color::Red)].into();
+    }
+    let name = args.join(" ");
+    let mut msg = Messages::new();
+    match ItemComparison::find(&name, character, &character.items) {
+        Some(comparison) => msg.extend(comparison.cli_display(), Color::Magenta),
+        None => msg.push(format!("No item named {name}"), Color::Red),
+    }
+    msg
+}
+
+pub(crate) fn shop(state: &mut GameState, args: &[&str]) -> Messages {
+    let Some(character) = state.character.as_mut() else {
+        return vec![("You have no character".to_string(), Color::Red)].into();
+    };
+    let shop = Shop::current();
+    let mut msg = Messages::new();
+    match args {
+        [] => {
+            msg.extend(shop.display_for_cli(), Color::Magenta);
+            msg.push(
+                format!("You have {} gold", character.items.gold),
+                Color::Yellow,
+            );
+        }
+        [number] => match number.parse().ok().and_then(|number| shop.get(number)) {
+            Some(item) => match shop::buy(&mut character.items, item.clone()) {
+                Ok(item) => msg.push(format!("You bought {}", item.name), Color::Green),
+                Err(e) => msg.push(e.to_string(), Color::Red),
+            },
+            None => msg.push(format!("There is no item {number} in the shop"), Color::Red),
+        },
+        _ => msg.push(
+            "Usage ( shop [ optional ( number ) ] )".to_string(),
+            Color::Red,
+        ),
+    }
+    msg
+}
+
+pub(crate) fn gamble(state: &mut GameState, _args: &[&str]) -> Messages {
+    let Some(character) = state.character.as_mut() else {
+        return vec![("You have no character".to_string(), Color::Red)].into();
+    };
+    let mut msg = Messages::new();
+    match shop::gamble(&mut character.items, &mut state.rng) {
+        Ok(item) => {
+            msg.push(format!("The gambler hands you {}", item.name), Color::Green);
+            msg.extend(item.cli_display(), Color::Magenta);
+        }
+        Err(e) => msg.push(e.to_string(), Color::Red),
+    }
+    msg
+}
+
+pub(crate) fn promote(state: &mut GameState, args: &[&str]) -> Messages {
+    let Some(character) = state.character.as_mut() else {
+        return vec![("You have no character".to_string(), Color::Red)].into();
+    };
+    let mut msg = Messages::new();
+    match args
+        .first()
+        .map(|class| Classes::try_from(class.to_string()))
+    {
+        Some(Ok(class)) => match character.promote(class) {
+            Ok(()) => msg.push(format!("You are now a {class}"), Color::Green),
+            Err(e) => msg.push(e.to_string(), Color::Red),
+        },
+        _ => {
+            msg.push(
+                format!(
+                    "A {} can be promoted at level {PROMOTION_LEVEL} to one of",
+                    character.class
+                ),
+                Color::Red,
+            );
+            msg.extend(
+                character
+                    .class
+                    .promotions()
+                    .iter()
+                    .map(|class| class.to_string())
+                    .collect(),
+                Color::Magenta,
+            );
+        }
+    }
+    msg
+}
+
+pub(crate) fn multiclass(state: &mut GameState, args: &[&str]) -> Messages {
+    let Some(character) = state.character.as_mut() else {
+        return vec![("You have no character".to_string(), Color::Red)].into();
+    };
+    let mut msg = Messages::new();
+    match args
+        .first()
+        .map(|class| Classes::try_from(class.to_string()))
+    {
+        Some(Ok(class)) => match character.multiclass(class) {
+            Ok(()) => msg.push(format!("You are now also a {class}"), Color::Green),
+            Err(e) => msg.push(e.to_string(), Color::Red),
+        },
+        _ => {
+            msg.push(
+                format!("From level {MULTICLASS_LEVEL} you can pick a secondary class"),
+                Color::Red,
+            );
+            msg.extend(
+                character
+                    .secondary_classes()
+                    .iter()
+                    .map(|class| class.to_string())
+                    .collect(),
+                Color::Magenta,
+            );
+        }
+    }
+    msg
+}
+
+pub(crate) fn target(state: &mut GameState, args: &[&str]) -> Messages {
+    let Some(character) = state.character.as_mut() else {
+        return vec![("You have no character".to_string(), Color::Red)].into();
+    };
+    let mut msg = Messages::new();
+    match args
+        .first()
+        .map(|targeting| Targeting::try_from(targeting.to_string()))
+    {
+        Some(Ok(targeting)) => {
+            character.targeting = targeting;
+            msg.push(format!("Now targeting {targeting}"), Color::Green);
+        }
+        _ => {
+            msg.push(
+                format!(
+                    "You target {}, area skills hit every enemy",
+                    character.targeting
+                ),
+                Color::Red,
+            );
+            msg.extend(Targeting::valid_flat(), Color::Magenta);
+        }
+    }
+    msg
+}
+
+pub(crate) fn record(state: &mut GameState, _args: &[&str]) -> Messages {
+    let Some(log) = state.last_battle.as_ref() else {
+        return vec![("There is no battle to record".to_string(), Color::Red)].into();
+    };
+    match log.save() {
+        Ok(path) => vec![(
+            format!("Battle log saved to {}", path.display()),
+            Color::Green,
+        )]
+        .into(),
+        Err(e) => vec![(format!("Failed to save battle log: {e}"), Color::Red)].into(),
+    }
+}
+
+pub(crate) fn replay(_state: &mut GameState, args: &[&str]) -> Messages {
+    let Some(path) = args.first() else {
+        return vec![("Usage ( replay [ file ] )".to_string(), Color::Red)].into();
+    };
+    let log = match BattleLog::read(Path::new(path)) {
+        Ok(log) => log,
+        Err(e) => return vec![(format!("Failed to read battle log: {e}"), Color::Red)].into(),
+    };
+    let mut messages = Messages::new();
+    match log.verify() {
+        Ok(replayed) => {
+            messages.extend(
+                replayed.to_string().lines().map(String::from).collect(),
+                Color::White,
+            );
+            messages.push("The replay matches the log".to_string(), Color::Green);
+        }
+        Err(e) => messages.push(e.to_string(), Color::Red),
+    }
+    messages
+}
+
 pub(crate) fn help(_state: &mut GameState, _args: &[&str]) -> Messages {
     COMMANDS
         .iter()
@@ -499,12 +867,22 @@ pub(crate) static BATTLE_COMMANDS: [Command; 5] = [
     },
 ];
 
-pub(crate) static COMMANDS: [Command; 11] = [
+pub(crate) static COMMANDS: [Command; 22] = [
     Command {
         command: "trait",
         help: "Select a trait - usage ( trait [ name ] ) ",
         program: select_trait,
     },
+    Command {
+        command: "boost",
+        help: "Spend a trait point on an attribute - usage ( boost [ attribute ] ) ",
+        program: boost,
+    },
+    Command {
+        command: "respec",
+        help: "Pay gold to take back your traits and boosts - usage ( respec ) ",
+        program: respec,
+    },
     Command {
         command: "eq",
         help: "Display your equipment - usage ( eq )",
@@ -527,7 +905,7 @@ pub(crate) static COMMANDS: [Command; 11] = [
     },
     Command {
         command: "skill",
-        help: "Change your skill or display your current skill - usage ( skill [ optional ( skill ) ] )",
+        help: "Change your skill, set a rotation of up to 4 skills or display your current skill - usage ( skill [ optional ( skill ) | ( [ round_robin | priority ] skill[:hp<30 | :vulnerable] ... ) ] )",
         program: change_skill,
     },
     Command {
@@ -550,6 +928,51 @@ pub(crate) static COMMANDS: [Command; 11] = [
         help: "Travel to a new location - usage ( travel [ direction ] )",
         program: travel,
     },
+    Command {
+        command: "loadout",
+        help: "Save, swap or list equipment loadouts - usage ( loadout [ optional ( save | delete ) ] [ name ] )",
+        program: loadout,
+    },
+    Command {
+        command: "inspect",
+        help: "Inspect an item and compare it with your equipment - usage ( inspect [ item ] )",
+        program: inspect,
+    },
+    Command {
+        command: "shop",
+        help: "Browse the shop or buy an item - usage ( shop [ optional ( number ) ] )",
+        program: shop,
+    },
+    Command {
+        command: "gamble",
+        help: "Pay the gambler for a random item - usage ( gamble )",
+        program: gamble,
+    },
+    Command {
+        command: "promote",
+        help: "Promote your class once you reach the promotion level - usage ( promote [ class ] )",
+        program: promote,
+    },
+    Command {
+        command: "multiclass",
+        help: "Pick a secondary class - usage ( multiclass [ class ] )",
+        program: multiclass,
+    },
+    Command {
+        command: "target",
+        help: "Pick which enemy of a group your skills hit - usage ( target [ first | weakest | strongest | vulnerable ] )",
+        program: target,
+    },
+    Command {
+        command: "record",
+        help: "Save the log of your last battle - usage ( record )",
+        program: record,
+    },
+    Command {
+        command: "replay",
+        help: "Replay a saved battle log - usage ( replay [ file ] )",
+        program: replay,
+    },
     Command {
         command: "help",
         help: "Prints the help menu - usage ( help )",
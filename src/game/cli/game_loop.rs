@@ -1,4 +1,10 @@
-use crate::{character::Character, class::Classes, ui::cli::GameClient};
+use crate::{
+    character::Character,
+    class::Classes,
+    replay::{BattleLog, BattleRecorder},
+    rng::CarrionRng,
+    ui::cli::GameClient,
+};
 
 use crossterm::style::Color;
 
@@ -30,6 +36,14 @@ impl GameStates {
     pub fn new() -> Self {
         Self::default()
     }
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            current_state: GameState {
+                rng: CarrionRng::new(seed),
+                ..Default::default()
+            },
+        }
+    }
     pub fn command(&mut self, command: String, client: &mut GameClient) {
         self.current_state.command(command, client);
     }
@@ -41,6 +55,9 @@ pub(crate) struct GameState {
     pub(crate) character: Option<Character>,
     pub(crate) location: Locations,
     pub(crate) state: State,
+    pub(crate) rng: CarrionRng,
+    pub(crate) battle: Option<BattleRecorder>,
+    pub(crate) last_battle: Option<BattleLog>,
 }
 
 impl GameState {
@@ -73,6 +90,29 @@ impl GameState {
         self.character.as_mut().unwrap()
     }
 
+    /// Hands out the rewards of the battle being recorded and moves its log into
+    /// `last_battle`, saving it straight away when the player died so it can be replayed later.
+    pub(crate) fn finish_battle(&mut self) -> Option<String> {
+        let BattleRecorder {
+            log, battle_info, ..
+        } = self.battle.take()?;
+        if let Some(character) = self.character.as_mut() {
+            character.status.clear();
+            character.buffs.clear();
+            character.claim_rewards(&battle_info);
+        }
+        let saved = if log.player_killed() {
+            Some(match log.save() {
+                Ok(path) => format!("Battle log saved to {}", path.display()),
+                Err(e) => format!("Failed to save battle log: {e}"),
+            })
+        } else {
+            None
+        };
+        self.last_battle = Some(log);
+        saved
+    }
+
     fn command(&mut self, command: String, client: &mut GameClient) {
         match self.state.clone() {
             State::Null => {
@@ -1,4 +1,5 @@
 use crate::{
+    encounter::{self, MAX_ENCOUNTER},
     enemy::{Enemy, Mob, MobGrade},
     ui::cli::TerminalMessages,
 };
@@ -36,8 +37,8 @@ impl Locations {
         }
     }
 
-    pub(crate) fn enemy(&self) -> Option<&Enemy> {
-        self.current().enemy.as_ref()
+    pub(crate) fn enemies(&self) -> &[Enemy] {
+        &self.current().enemies
     }
 
     pub(crate) fn tag_enemy(&mut self) {
@@ -45,9 +46,9 @@ impl Locations {
         self.get_mut(&coordinates).unwrap().tagged = true;
     }
 
-    pub(crate) fn enemy_killed(&mut self) {
+    pub(crate) fn enemies_killed(&mut self) {
         let coordinates = self.current;
-        self.get_mut(&coordinates).unwrap().enemy = None;
+        self.get_mut(&coordinates).unwrap().enemies.clear();
     }
 
     fn get_mut(&mut self, coords: &Coordinates) -> Option<&mut Location> {
@@ -138,7 +139,7 @@ impl Locations {
             crossterm::style::Color::Magenta,
         ));
 
-        if state.location.current().tagged && state.location.current().enemy.is_some() {
+        if state.location.current().tagged && !state.location.current().enemies.is_empty() {
             msg.push((
                 "You have been here before...and the locals remember you".to_string(),
                 crossterm::style::Color::Red,
@@ -146,7 +147,7 @@ impl Locations {
             msg.push((
                 format!(
                     "Enemy {enemy} prepares their attack",
-                    enemy = state.location.current().enemy.as_ref().unwrap().kind
+                    enemy = encounter::describe(&state.location.current().enemies)
                 ),
                 crossterm::style::Color::Red,
             ));
@@ -155,22 +156,38 @@ impl Locations {
             return msg;
         }
 
-        if let Some(mob) = mob_generation_algo(state.location.current()) {
-            if state.location.current().enemy.is_some() && state.character.is_some() {
+        if let Some(mob) = mob_generation_algo(state.location.current(), &mut state.rng) {
+            if !state.location.current().enemies.is_empty() && state.character.is_some() {
                 msg.push((
                     format!(
                         "You see a {enemy} in the distance",
-                        enemy = state.location.current().enemy.as_ref().unwrap().kind
+                        enemy = encounter::describe(&state.location.current().enemies)
                     ),
                     crossterm::style::Color::Red,
                 ));
                 return msg;
             }
-            let enemy = mob.generate(state.character.as_ref().unwrap().level);
+            // Groups get bigger the further out the location is
+            let size = state
+                .rng
+                .gen_range(1..=group_size(state.location.current()));
+            let mut mobs = vec![mob];
+            for _ in 1..size {
+                mobs.extend(mob_generation_algo(
+                    state.location.current(),
+                    &mut state.rng,
+                ));
+            }
+            let level = state.character.as_ref().unwrap().level;
+            let enemies: Vec<Enemy> = mobs
+                .iter()
+                .map(|mob| mob.generate(level, &mut state.rng))
+                .collect();
+            let group = encounter::describe(&enemies);
             let coordinates = state.location.current;
-            state.location.get_mut(&coordinates).unwrap().enemy = Some(enemy);
+            state.location.get_mut(&coordinates).unwrap().enemies = enemies;
             msg.push((
-                format!("You see a {enemy} in the distance", enemy = mob),
+                format!("You see a {enemy} in the distance", enemy = group),
                 // Light Red
                 crossterm::style::Color::Rgb {
                     r: 255,
@@ -187,13 +204,9 @@ impl Locations {
         self.current = (1, 1, 1);
     }
 
-    pub(crate) fn get_mut_enemy(&mut self) -> &mut Enemy {
+    pub(crate) fn get_mut_enemies(&mut self) -> &mut Vec<Enemy> {
         let coordinates = self.current;
-        self.get_mut(&coordinates)
-            .unwrap()
-            .enemy
-            .as_mut()
-            .expect("No enemy found")
+        &mut self.get_mut(&coordinates).unwrap().enemies
     }
 }
 
@@ -203,7 +216,7 @@ struct Location {
     y: i32,
     z: u8,
     directions: Vec<ValidDirections>,
-    enemy: Option<Enemy>,
+    enemies: Vec<Enemy>,
     tagged: bool,
     descriptor: String,
 }
@@ -269,23 +282,33 @@ fn load_descriptions() -> Vec<Description> {
     serde_json::from_reader(reader).expect("Failed to parse JSON")
 }
 
+fn distance(location: &Location) -> f64 {
+    ((location.x.pow(2) + location.y.pow(2) + location.z.pow(2) as i32) as f64).sqrt()
+}
+
+/// Most enemies met at once at a location, lone mobs near the center
+fn group_size(location: &Location) -> usize {
+    match distance(location) {
+        d if d <= 5.0 => 1,
+        d if d <= 10.0 => 2,
+        _ => MAX_ENCOUNTER,
+    }
+}
+
 /// The further we are from 0,0,0 the harder enemies should get
 /// MobGrade increases as we go further from the center
-fn mob_generation_algo(location: &Location) -> Option<Mob> {
-    if location.enemy.is_some() {
-        return Some(location.enemy.as_ref().unwrap().kind);
+fn mob_generation_algo(location: &Location, rng: &mut impl Rng) -> Option<Mob> {
+    if let Some(enemy) = location.enemies.first() {
+        return Some(enemy.kind);
     }
-    if !rand::thread_rng().gen_bool(0.85) {
+    if !rng.gen_bool(0.85) {
         return None;
     }
-    let mut rng = rand::thread_rng();
 
-    let distance =
-        ((location.x.pow(2) + location.y.pow(2) + location.z.pow(2) as i32) as f64).sqrt();
-    let grade = match distance {
+    let grade = match distance(location) {
         d if d <= 5.0 => {
             let dist = WeightedIndex::new(vec![50, 40, 10]).unwrap();
-            match dist.sample(&mut rng) {
+            match dist.sample(rng) {
                 0 => MobGrade::Weak,
                 1 => MobGrade::Normal,
                 _ => MobGrade::Strong,
@@ -293,7 +316,7 @@ fn mob_generation_algo(location: &Location) -> Option<Mob> {
         }
         d if d <= 10.0 => {
             let dist = WeightedIndex::new(vec![20, 30, 30, 20]).unwrap();
-            match dist.sample(&mut rng) {
+            match dist.sample(rng) {
                 0 => MobGrade::Weak,
                 1 => MobGrade::Normal,
                 2 => MobGrade::Strong,
@@ -302,7 +325,7 @@ fn mob_generation_algo(location: &Location) -> Option<Mob> {
         }
         d if d <= 15.0 => {
             let dist = WeightedIndex::new(vec![10, 20, 30, 30, 10]).unwrap();
-            match dist.sample(&mut rng) {
+            match dist.sample(rng) {
                 0 => MobGrade::Weak,
                 1 => MobGrade::Normal,
                 2 => MobGrade::Strong,
@@ -312,7 +335,7 @@ fn mob_generation_algo(location: &Location) -> Option<Mob> {
         }
         _ => {
             let dist = WeightedIndex::new(vec![10, 20, 20, 30, 20]).unwrap();
-            match dist.sample(&mut rng) {
+            match dist.sample(rng) {
                 0 => MobGrade::Weak,
                 1 => MobGrade::Normal,
                 2 => MobGrade::Strong,
@@ -323,7 +346,7 @@ fn mob_generation_algo(location: &Location) -> Option<Mob> {
         }
     };
 
-    Some(grade.into())
+    Some(Mob::from_grade(grade, rng))
 }
 
 fn valid_direction_map(
@@ -372,7 +395,7 @@ fn generate_map() -> HashMap<(i32, i32, u8), Location> {
             y: desc.y,
             z: desc.z,
             directions,
-            enemy: None,
+            enemies: vec![],
             tagged: false,
             descriptor: desc.descriptor.clone(),
         };
@@ -3,12 +3,17 @@ use crate::database::{Consumer, Database};
 use crate::{database::Producer, item::Items};
 
 use crate::character::Character;
-use crate::enemy::Mob;
+use crate::encounter::encounter;
 use crate::game::character_data::CharacterData;
 use crate::game::mutations::Mutations;
-use crate::BattleInfo;
+use crate::game::party::{self, LootRule, Party};
+use crate::game::trade::Trade;
+use crate::replay::BattleLog;
+use crate::rng::CarrionRng;
+use crate::{BattleInfo, CarrionResult};
 use dashmap::DashMap;
-use rand::random;
+use rand::Rng;
+use tokio::sync::Mutex;
 
 use tracing::{info, trace, warn};
 
@@ -16,10 +21,17 @@ use super::buffer::Buffer;
 
 pub struct GameData {
     pub characters: DashMap<u64, CharacterData>,
+    // open trades keyed by the sender, holding what was offered in escrow
+    trades: DashMap<u64, Trade>,
+    // parties keyed by their id, kept in memory only
+    parties: DashMap<u64, Party>,
+    // raid results waiting for the members who did not lead the raid
+    raid_results: DashMap<u64, BattleResult>,
     producer: Box<dyn Producer + Sync + Send>,
     consumer: Box<dyn Consumer + Sync + Send>,
     database: Database,
     buffer: Buffer,
+    rng: Mutex<CarrionRng>,
 }
 
 impl Default for GameData {
@@ -38,16 +50,27 @@ impl GameData {
             game_data.insert(c.user_id, character_data);
         }
         self.characters = game_data.clone();
-        self.activate_enemies();
+        for trade in self.consumer.get_trades().await.unwrap_or_default() {
+            self.trades.insert(trade.from, trade);
+        }
+        self.activate_enemies().await;
     }
 
     pub fn new(database: Database) -> Self {
+        Self::with_seed(database, rand::random())
+    }
+
+    pub fn with_seed(database: Database, seed: u64) -> Self {
         Self {
             characters: DashMap::new(),
+            trades: DashMap::new(),
+            parties: DashMap::new(),
+            raid_results: DashMap::new(),
             producer: database.get_producer(),
             consumer: database.get_consumer(),
             database,
             buffer: Buffer::new(),
+            rng: Mutex::new(CarrionRng::new(seed)),
         }
     }
 
@@ -63,35 +86,70 @@ impl GameData {
         self.characters.get(&user_id).map(|c| c.items.clone())
     }
 
-    fn activate_enemies(&self) {
+    pub fn get_trade(&self, from: u64) -> Option<Trade> {
+        self.trades.get(&from).map(|t| t.clone())
+    }
+
+    /// Every open trade the user sent or received.
+    pub fn get_trades(&self, user_id: u64) -> Vec<Trade> {
+        self.trades
+            .iter()
+            .filter(|t| t.from == user_id || t.to == user_id)
+            .map(|t| t.clone())
+            .collect()
+    }
+
+    /// The party the user is a member of.
+    pub fn get_party(&self, user_id: u64) -> Option<Party> {
+        self.parties
+            .iter()
+            .find(|p| p.is_member(user_id))
+            .map(|p| p.clone())
+    }
+
+    async fn activate_enemies(&self) {
+        let mut rng = self.rng.lock().await;
         for mut character in self.characters.iter_mut() {
-            if character.active_enemy.is_some() {
-                return;
+            if !character.active_enemies.is_empty() {
+                continue;
             }
 
-            if character.enemies.is_empty() {
-                let mob: Mob = random();
-                let enemy = mob.generate(character.character.level);
-                character.active_enemy = Some(enemy.clone());
-                return;
+            if let Some(run) = &character.character.dungeon {
+                character.active_enemies = run.enemy(&mut *rng).into_iter().collect();
+                continue;
             }
 
-            let enemy = character.enemies.remove(0);
-            let enemy = enemy.generate(character.character.level);
-            character.active_enemy = Some(enemy.clone());
+            let zone = character.character.zone;
+            let level = character.character.level;
+            character.active_enemies = encounter(&mut character.enemies, zone, level, &mut *rng);
         }
     }
 
     pub async fn battle(&self, character: u64) -> BattleResult {
+        if let Some((_, raid)) = self.raid_results.remove(&character) {
+            return raid;
+        }
+        if let Some(raid) = self.raid(character).await {
+            return raid;
+        }
         let mut battles = BattleResult::default();
-        if let Some(c) = self.characters.get(&character) {
-            let enemy = c.active_enemy.as_ref().unwrap();
-            let mut battle_info = BattleInfo::begin(&c.character, enemy);
-            while !battle_info.enemy_killed && !battle_info.player_killed {
-                c.character.player_attack(enemy, &mut battle_info);
-
-                if !battle_info.enemy_killed {
-                    c.character.enemy_attack(enemy, &mut battle_info);
+        let data = self.characters.get(&character).map(|c| c.clone());
+        if let Some(c) = data {
+            if !c.character.can_battle() {
+                self.buffer.add(Mutations::Recover(character));
+                return battles;
+            }
+            // Enemies are activated on the next tick
+            if c.active_enemies.is_empty() {
+                return battles;
+            }
+            let seed = self.rng.lock().await.gen();
+            let (mut battle_info, log) = BattleLog::synchro(&c.character, &c.active_enemies, seed);
+            battle_info.discovered = c.discoveries(&battle_info).await;
+            if battle_info.player_killed {
+                match log.save() {
+                    Ok(path) => info!("Saved battle log to {}", path.display()),
+                    Err(e) => warn!("Failed to save battle log: {:?}", e),
                 }
             }
             self.apply_battle_info(&battle_info, c.user_id);
@@ -101,10 +159,62 @@ impl GameData {
         battles
     }
 
+    /// Fights the raid of the party `leader` leads once its boss has spawned, with every member
+    /// who can battle. The other members get the same result on their next heartbeat.
+    /// Raids roll on the game rng and are not replayable, see `party::raid`.
+    async fn raid(&self, leader: u64) -> Option<BattleResult> {
+        let id = self.get_party(leader).filter(|p| p.leader == leader)?.id;
+        let (mut boss, members) = {
+            let mut party = self.parties.get_mut(&id)?;
+            (party.raid.take()?, party.members.clone())
+        };
+        let (raiders, mut characters): (Vec<u64>, Vec<Character>) = members
+            .iter()
+            .filter_map(|member| self.get_character(*member).map(|c| (*member, c)))
+            .filter(|(_, character)| character.can_battle())
+            .unzip();
+        if characters.is_empty() {
+            if let Some(mut party) = self.parties.get_mut(&id) {
+                party.raid = Some(boss);
+            }
+            return None;
+        }
+
+        let mut infos = {
+            let mut rng = self.rng.lock().await;
+            let mut infos = party::raid(&mut characters, &mut boss, &mut *rng);
+            if let Some(mut party) = self.parties.get_mut(&id) {
+                party.distribute(&boss, &mut infos, &mut *rng);
+            }
+            infos
+        };
+        for (raider, battle_info) in raiders.iter().zip(infos.iter_mut()) {
+            battle_info.monster_name = boss.kind.to_string();
+            self.buffer.extend(vec![
+                Mutations::UpdatePlayer(*raider, battle_info.clone()),
+                Mutations::UpdateSkills(*raider, battle_info.clone()),
+                Mutations::NewItems(*raider, (&*battle_info).into()),
+            ]);
+        }
+        info!(
+            "Party {} raided {}, killed: {}",
+            id,
+            boss.kind,
+            !boss.alive()
+        );
+
+        let result = BattleResult::raid(infos);
+        for raider in raiders.iter().filter(|raider| **raider != leader) {
+            self.raid_results.insert(*raider, result.clone());
+        }
+        Some(result)
+    }
+
     pub fn apply_battle_info(&self, battle_info: &BattleInfo, character_id: u64) {
         let mutations = vec![
             Mutations::UpdatePlayer(character_id, battle_info.clone()),
             Mutations::UpdateEnemies(character_id, battle_info.clone()),
+            Mutations::UpdateSkills(character_id, battle_info.clone()),
             Mutations::NewItems(character_id, battle_info.into()),
         ];
         self.buffer.extend(mutations);
@@ -137,9 +247,26 @@ impl GameData {
                             warn!("Failed to delete user items: {:?}", e);
                         });
 
+                    for trade in self.get_trades(*user_id) {
+                        self.decline_trade(trade.to, trade.from).await;
+                    }
+                    self.leave_party(*user_id);
+
                     info!("Deleted character: {}", user_id);
                 }
 
+                Mutations::OfferTrade(from, to, item, gold) => {
+                    self.offer_trade(*from, *to, item.as_deref(), *gold).await;
+                }
+
+                Mutations::AcceptTrade(to, from) => {
+                    self.accept_trade(*to, *from).await;
+                }
+
+                Mutations::DeclineTrade(user_id, from) => {
+                    self.decline_trade(*user_id, *from).await;
+                }
+
                 Mutations::Create(character) => {
                     let character_data = CharacterData::init(character, self.database).await;
                     self.characters.insert(character.user_id, character_data);
@@ -150,28 +277,195 @@ impl GameData {
                         .map_err(|e| {
                             warn!("Failed to store character: {:?}", e);
                         });
-                    self.activate_enemies();
+                    self.activate_enemies().await;
                     info!("Created character: {}", character.user_id);
                 }
 
+                Mutations::CreateParty(user_id, loot) => {
+                    self.create_party(*user_id, *loot).await;
+                }
+
+                Mutations::InviteParty(from, to) => {
+                    if self.get_party(*to).is_some() || !self.characters.contains_key(to) {
+                        warn!("{} can not be invited to a party", to);
+                    } else {
+                        self.update_party(*from, |party| party.invite(*from, *to));
+                    }
+                }
+
+                Mutations::JoinParty(user_id, member) => {
+                    if self.get_party(*user_id).is_some() {
+                        warn!("{} is already in a party", user_id);
+                    } else {
+                        self.update_party(*member, |party| party.join(*user_id));
+                    }
+                }
+
+                Mutations::LeaveParty(user_id) => {
+                    self.leave_party(*user_id);
+                }
+
+                Mutations::StartRaid(user_id) => {
+                    self.start_raid(*user_id).await;
+                }
+
                 _ => {}
             }
         }
     }
 
+    async fn create_party(&self, user_id: u64, loot: LootRule) {
+        if self.get_party(user_id).is_some() || !self.characters.contains_key(&user_id) {
+            warn!("Party for {} can not be created", user_id);
+            return;
+        }
+        let id = self.rng.lock().await.gen();
+        self.parties.insert(id, Party::new(id, user_id, loot));
+        info!("Party {} created by {}", id, user_id);
+    }
+
+    /// Applies `update` to the party `member` is in.
+    fn update_party(&self, member: u64, update: impl FnOnce(&mut Party) -> CarrionResult<()>) {
+        let Some(id) = self.get_party(member).map(|party| party.id) else {
+            warn!("{} is not in a party", member);
+            return;
+        };
+        let Some(mut party) = self.parties.get_mut(&id) else {
+            return;
+        };
+        if let Err(e) = update(&mut party) {
+            warn!("Failed to update party {}: {}", id, e);
+        }
+    }
+
+    fn leave_party(&self, user_id: u64) {
+        let Some(id) = self.get_party(user_id).map(|party| party.id) else {
+            return;
+        };
+        if self
+            .parties
+            .remove_if_mut(&id, |_, party| party.leave(user_id))
+            .is_some()
+        {
+            info!("Party {} disbanded", id);
+        }
+    }
+
+    async fn start_raid(&self, user_id: u64) {
+        let Some(party) = self.get_party(user_id) else {
+            warn!("{} is not in a party", user_id);
+            return;
+        };
+        let levels: Vec<u32> = party
+            .members
+            .iter()
+            .filter_map(|member| self.get_character(*member))
+            .map(|character| character.level)
+            .collect();
+        let mut rng = self.rng.lock().await;
+        self.update_party(user_id, |party| {
+            party.start_raid(user_id, &levels, &mut *rng).map(|_| ())
+        });
+    }
+
+    async fn offer_trade(&self, from: u64, to: u64, item: Option<&str>, gold: u64) {
+        if self.trades.contains_key(&from) || !self.characters.contains_key(&to) {
+            warn!("Trade from {} to {} can not be offered", from, to);
+            return;
+        }
+        let Some(mut sender) = self.characters.get_mut(&from) else {
+            return;
+        };
+        let trade = match Trade::offer(from, to, item, gold, &mut sender.items) {
+            Ok(trade) => trade,
+            Err(e) => {
+                warn!("Failed to offer trade: {}", e);
+                return;
+            }
+        };
+        let items = sender.items.clone();
+        drop(sender);
+
+        self.trades.insert(from, trade.clone());
+        self.store_trade_items(from, items).await;
+        let _ = self.producer.store_trade(trade).await.map_err(|e| {
+            warn!("Failed to store trade: {:?}", e);
+        });
+        info!("Trade offered from {} to {}", from, to);
+    }
+
+    async fn accept_trade(&self, to: u64, from: u64) {
+        let Some((_, trade)) = self.trades.remove_if(&from, |_, trade| trade.to == to) else {
+            warn!("No trade from {} to {}", from, to);
+            return;
+        };
+        self.resolve_trade(trade, to).await;
+    }
+
+    /// The receiver declining or the sender cancelling both hand the escrow back to the sender.
+    async fn decline_trade(&self, user_id: u64, from: u64) {
+        let Some((_, trade)) = self.trades.remove_if(&from, |_, trade| {
+            trade.to == user_id || trade.from == user_id
+        }) else {
+            warn!("No trade from {} for {}", from, user_id);
+            return;
+        };
+        self.resolve_trade(trade, from).await;
+    }
+
+    /// Delivers an escrowed trade to `receiver`. The trade has already been taken out of
+    /// escrow so it can not resolve twice, it goes back in if the receiver can not take it.
+    async fn resolve_trade(&self, trade: Trade, receiver: u64) {
+        let Some(mut character) = self.characters.get_mut(&receiver) else {
+            if receiver == trade.from {
+                warn!("Dropping trade from deleted character {}", trade.from);
+                let _ = self.producer.delete_trade(trade.from).await;
+            } else {
+                self.trades.insert(trade.from, trade);
+            }
+            return;
+        };
+        let from = trade.from;
+        if let Err(e) = trade.deliverable(&character.items) {
+            warn!("Failed to resolve trade: {}", e);
+            drop(character);
+            self.trades.insert(from, trade);
+            return;
+        }
+        let _ = trade.deliver(&mut character.items);
+        let items = character.items.clone();
+        drop(character);
+
+        self.store_trade_items(receiver, items).await;
+        let _ = self.producer.delete_trade(from).await.map_err(|e| {
+            warn!("Failed to delete trade: {:?}", e);
+        });
+        info!("Trade from {} resolved to {}", from, receiver);
+    }
+
+    async fn store_trade_items(&self, user_id: u64, items: Items) {
+        let _ = self
+            .producer
+            .store_user_items(items, user_id)
+            .await
+            .map_err(|e| {
+                warn!("Failed to store items: {:?}", e);
+            });
+    }
+
     pub async fn apply_mutations(&self, character: u64) {
         trace!("Applying Mutations");
 
-        if let Some(buffer) = self.buffer.get(&character) {
+        if let Some(buffer) = self.buffer.take(character) {
+            // Only a seed is taken from the game rng, so database writes don't hold its lock
+            let seed = self.rng.lock().await.gen();
+            let mut rng = CarrionRng::new(seed);
             for mutation in buffer.iter() {
                 if let Some(mut c) = self.characters.get_mut(mutation.user_id()) {
-                    c.apply_mutation(mutation.clone()).await;
+                    c.apply_mutation(mutation.clone(), &mut rng).await;
                 }
             }
-        }
-
-        if let Some(buffer) = self.buffer.get(&character) {
-            self.apply_global_mutations(buffer.clone()).await;
+            self.apply_global_mutations(buffer).await;
         }
     }
 
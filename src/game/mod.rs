@@ -3,3 +3,5 @@ pub mod character_data;
 pub mod cli;
 pub mod data;
 pub mod mutations;
+pub mod party;
+pub mod trade;
@@ -1,25 +1,54 @@
 use crate::character::Character;
+use crate::class::Classes;
+use crate::encounter::Targeting;
 
 use crate::enemy::Mob;
+use crate::game::party::LootRule;
 use crate::item::{EquipmentSlot, IndividualItem, Items};
 use crate::r#trait::CharacterTraits;
+use crate::rotation::Rotation;
 use crate::skill::Skill;
+use crate::zone::Zone;
 use crate::BattleInfo;
 
 impl Mutations {
     pub fn user_id(&self) -> &u64 {
         match self {
             Mutations::Skill(user_id, _) => user_id,
+            Mutations::Rotation(user_id, _) => user_id,
+            Mutations::Targeting(user_id, _) => user_id,
             Mutations::Equip(user_id, _) => user_id,
             Mutations::Delete(user_id) => user_id,
             Mutations::Create(character) => &character.user_id,
             Mutations::Trait(user_id, _) => user_id,
+            Mutations::BoostAttribute(user_id, _) => user_id,
+            Mutations::Respec(user_id) => user_id,
             Mutations::AddEnemy(user_id, _, _) => user_id,
             Mutations::Sell(user_id, _, _) => user_id,
             Mutations::NewItems(user_id, _) => user_id,
             Mutations::UpdateEnemies(user_id, _) => user_id,
             Mutations::UpdatePlayer(user_id, _) => user_id,
             Mutations::UpdateSkills(user_id, _) => user_id,
+            Mutations::Rest(user_id) => user_id,
+            Mutations::Recover(user_id) => user_id,
+            Mutations::SaveLoadout(user_id, _) => user_id,
+            Mutations::SwapLoadout(user_id, _) => user_id,
+            Mutations::DeleteLoadout(user_id, _) => user_id,
+            Mutations::Buy(user_id, _) => user_id,
+            Mutations::Gamble(user_id) => user_id,
+            Mutations::OfferTrade(user_id, _, _, _) => user_id,
+            Mutations::AcceptTrade(user_id, _) => user_id,
+            Mutations::DeclineTrade(user_id, _) => user_id,
+            Mutations::CreateParty(user_id, _) => user_id,
+            Mutations::InviteParty(user_id, _) => user_id,
+            Mutations::JoinParty(user_id, _) => user_id,
+            Mutations::LeaveParty(user_id) => user_id,
+            Mutations::StartRaid(user_id) => user_id,
+            Mutations::Zone(user_id, _) => user_id,
+            Mutations::EnterDungeon(user_id) => user_id,
+            Mutations::AbandonDungeon(user_id) => user_id,
+            Mutations::Promote(user_id, _) => user_id,
+            Mutations::Multiclass(user_id, _) => user_id,
         }
     }
 }
@@ -27,10 +56,16 @@ impl Mutations {
 #[derive(Debug, Clone)]
 pub enum Mutations {
     Skill(u64, Skill),
+    Rotation(u64, Rotation),
+    Targeting(u64, Targeting),
     Equip(u64, IndividualItem),
     Delete(u64),
     Create(Box<Character>),
     Trait(u64, CharacterTraits),
+    // a trait point spent on the named attribute
+    BoostAttribute(u64, String),
+    // takes back every trait and attribute boost for gold
+    Respec(u64),
     AddEnemy(u64, Mob, u32),
     // user_id, optional slot to sell, and the items the user "knew" about before the sell
     Sell(u64, Option<EquipmentSlot>, Option<Items>),
@@ -39,4 +74,39 @@ pub enum Mutations {
     UpdateEnemies(u64, BattleInfo),
     UpdatePlayer(u64, BattleInfo),
     UpdateSkills(u64, BattleInfo),
+
+    Rest(u64),
+    // a heartbeat spent resting or waiting to respawn instead of fighting
+    Recover(u64),
+
+    SaveLoadout(u64, String),
+    SwapLoadout(u64, String),
+    DeleteLoadout(u64, String),
+
+    // the item the user picked from the shop stock they were shown
+    Buy(u64, IndividualItem),
+    Gamble(u64),
+
+    // sender, receiver, optional item name and gold
+    OfferTrade(u64, u64, Option<String>, u64),
+    // receiver, sender
+    AcceptTrade(u64, u64),
+    // user, sender of the trade, the sender declining their own offer cancels it
+    DeclineTrade(u64, u64),
+
+    CreateParty(u64, LootRule),
+    // leader, invited user
+    InviteParty(u64, u64),
+    // user, a member of the party they were invited to
+    JoinParty(u64, u64),
+    LeaveParty(u64),
+    StartRaid(u64),
+
+    Zone(u64, Zone),
+
+    EnterDungeon(u64),
+    AbandonDungeon(u64),
+
+    Promote(u64, Classes),
+    Multiclass(u64, Classes),
 }
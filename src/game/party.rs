@@ -0,0 +1,312 @@
+use std::fmt::Display;
+
+use eris_macro::{ErisDisplayEmoji, ErisValidEnum};
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use strum::EnumIter;
+
+use crate::character::Character;
+use crate::combat;
+use crate::enemy::{Enemy, Mob, MobGrade};
+use crate::{BattleInfo, CarrionError, CarrionResult};
+
+/// Most members in a party, invites included.
+pub const MAX_PARTY: usize = 4;
+/// Members needed to start a raid.
+pub const MIN_RAID: usize = 2;
+/// Levels the raid boss gains for every member after the first.
+pub const RAID_LEVEL_PER_MEMBER: u32 = 3;
+/// Rounds after which a raid nobody won is called off.
+pub const RAID_ROUNDS: u32 = 200;
+
+/// How the items of a raid boss are handed out. The gold is always split by contribution.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    Deserialize,
+    ErisValidEnum,
+    ErisDisplayEmoji,
+    EnumIter,
+)]
+pub enum LootRule {
+    /// Every item goes to a member picked by the share of the damage they dealt.
+    #[default]
+    #[emoji("📊")]
+    Contribution,
+    /// Members take turns getting an item, carried over from raid to raid.
+    #[emoji("🔄")]
+    RoundRobin,
+}
+
+/// Discord users fighting raid bosses together. Parties live in `GameData` keyed by their id
+/// and are not stored, a restart disbands them. The leader invites, invited users join and
+/// the leader starts a raid once there are enough members. The raid is fought on the
+/// leader's next heartbeat and every member gets their own `BattleInfo` of it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Party {
+    pub id: u64,
+    pub leader: u64,
+    pub members: Vec<u64>,
+    pub invited: Vec<u64>,
+    pub loot: LootRule,
+    next_looter: usize,
+    /// The boss of the raid waiting to be fought.
+    pub raid: Option<Enemy>,
+}
+
+impl Party {
+    pub fn new(id: u64, leader: u64, loot: LootRule) -> Self {
+        Self {
+            id,
+            leader,
+            members: vec![leader],
+            invited: vec![],
+            loot,
+            next_looter: 0,
+            raid: None,
+        }
+    }
+
+    pub fn is_member(&self, user_id: u64) -> bool {
+        self.members.contains(&user_id)
+    }
+
+    pub fn invite(&mut self, from: u64, to: u64) -> CarrionResult<()> {
+        if from != self.leader {
+            return Err(CarrionError::PartyError(
+                "Only the party leader can invite".to_string(),
+            ));
+        }
+        if self.is_member(to) || self.invited.contains(&to) {
+            return Err(CarrionError::PartyError(
+                "They are already in or invited to the party".to_string(),
+            ));
+        }
+        if self.members.len() + self.invited.len() >= MAX_PARTY {
+            return Err(CarrionError::PartyError(format!(
+                "A party has at most {} members",
+                MAX_PARTY
+            )));
+        }
+        self.invited.push(to);
+        Ok(())
+    }
+
+    pub fn join(&mut self, user_id: u64) -> CarrionResult<()> {
+        if self.raid.is_some() {
+            return Err(CarrionError::PartyError(
+                "The party is in the middle of a raid".to_string(),
+            ));
+        }
+        let Some(index) = self.invited.iter().position(|invited| *invited == user_id) else {
+            return Err(CarrionError::PartyError(
+                "You have not been invited to the party".to_string(),
+            ));
+        };
+        self.invited.remove(index);
+        self.members.push(user_id);
+        Ok(())
+    }
+
+    /// Takes the user out of the party, the next member leads if the leader left. Returns
+    /// whether the party is empty and should be disbanded.
+    pub fn leave(&mut self, user_id: u64) -> bool {
+        self.members.retain(|member| *member != user_id);
+        self.invited.retain(|invited| *invited != user_id);
+        if self.leader == user_id {
+            if let Some(leader) = self.members.first() {
+                self.leader = *leader;
+            }
+        }
+        self.members.is_empty()
+    }
+
+    /// Whether `user_id` may start a raid of this party right now.
+    pub fn can_raid(&self, user_id: u64) -> CarrionResult<()> {
+        if user_id != self.leader {
+            return Err(CarrionError::PartyError(
+                "Only the party leader can start a raid".to_string(),
+            ));
+        }
+        if self.raid.is_some() {
+            return Err(CarrionError::PartyError(
+                "A raid has already started".to_string(),
+            ));
+        }
+        if self.members.len() < MIN_RAID {
+            return Err(CarrionError::PartyError(format!(
+                "A raid needs at least {} members",
+                MIN_RAID
+            )));
+        }
+        Ok(())
+    }
+
+    /// Spawns a boss for the members at their `levels`, stronger the more of them there are.
+    pub fn start_raid(
+        &mut self,
+        user_id: u64,
+        levels: &[u32],
+        rng: &mut impl Rng,
+    ) -> CarrionResult<&Enemy> {
+        self.can_raid(user_id)?;
+        let level = levels.iter().copied().max().unwrap_or(1)
+            + RAID_LEVEL_PER_MEMBER * (levels.len().max(1) as u32 - 1);
+        let boss = Mob::from_grade(MobGrade::Boss, rng).generate(level, rng);
+        Ok(self.raid.insert(boss))
+    }
+
+    /// Shares out a dead boss between the members of `infos`. The killing blow paid the
+    /// whole boss to one member, instead every survivor gets its experience, the gold is split
+    /// by the damage each member dealt and the items go out by the party's `LootRule`.
+    pub fn distribute(&mut self, boss: &Enemy, infos: &mut [BattleInfo], rng: &mut impl Rng) {
+        if boss.alive() || infos.is_empty() {
+            return;
+        }
+        let damage: Vec<u64> = infos
+            .iter()
+            .map(|info| info.player_damage.max(0) as u64)
+            .collect();
+        let total = damage.iter().sum::<u64>().max(1);
+        for (info, damage) in infos.iter_mut().zip(&damage) {
+            info.enemy_killed = true;
+            info.item_gained.clear();
+            info.gold_gained = boss.gold * damage / total;
+            info.experience_gained = match info.player_killed {
+                true => 0,
+                false => boss.experience,
+            };
+            info.custom_text = Some(format!(
+                "Dealt {}% of the raid damage",
+                damage * 100 / total
+            ));
+        }
+        let top = (0..damage.len())
+            .max_by_key(|index| damage[*index])
+            .unwrap_or(0);
+        let paid: u64 = infos.iter().map(|info| info.gold_gained).sum();
+        infos[top].gold_gained += boss.gold - paid;
+
+        let weights = WeightedIndex::new(&damage).ok();
+        for item in &boss.items {
+            let looter = match (self.loot, &weights) {
+                (LootRule::Contribution, Some(weights)) => weights.sample(rng),
+                (LootRule::Contribution, None) => top,
+                (LootRule::RoundRobin, _) => {
+                    let looter = self.next_looter % infos.len();
+                    self.next_looter += 1;
+                    looter
+                }
+            };
+            infos[looter].item_gained.push(item.clone());
+        }
+    }
+}
+
+impl Display for Party {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let members: Vec<String> = self
+            .members
+            .iter()
+            .map(|member| format!("<@{}>", member))
+            .collect();
+        write!(
+            f,
+            "Party of <@{}>: {} - loot by {}",
+            self.leader,
+            members.join(", "),
+            self.loot
+        )?;
+        if !self.invited.is_empty() {
+            write!(f, ", {} invited", self.invited.len())?;
+        }
+        if let Some(boss) = &self.raid {
+            write!(f, ", raiding {} level {}", boss.kind, boss.level)?;
+        }
+        Ok(())
+    }
+}
+
+/// Fights `boss` with every member until it or the whole party is dead, one `BattleInfo` per
+/// member. Members take their rounds in party order against the shared boss, so it strikes
+/// back at whoever just attacked it.
+///
+/// Raids are not recorded: a `BattleLog` holds a single character, so unlike discord and cli
+/// battles a raid can not be replayed and leaves no log behind when a member dies.
+pub fn raid(members: &mut [Character], boss: &mut Enemy, rng: &mut impl Rng) -> Vec<BattleInfo> {
+    let mut infos: Vec<BattleInfo> = members
+        .iter()
+        .map(|member| BattleInfo::begin(member, std::slice::from_ref(boss)))
+        .collect();
+    for _ in 0..RAID_ROUNDS {
+        for (member, info) in members.iter_mut().zip(infos.iter_mut()) {
+            if boss.alive() && !info.player_killed {
+                combat::round(member, std::slice::from_mut(boss), info, rng);
+            }
+        }
+        if !boss.alive() || infos.iter().all(|info| info.player_killed) {
+            break;
+        }
+    }
+    infos
+}
+
+#[cfg(test)]
+mod test {
+    use super::{raid, LootRule, Party, MAX_PARTY};
+    use crate::{character::Character, class::Classes, rng::CarrionRng};
+
+    #[test]
+    fn raids_share_the_boss_between_members() {
+        let mut rng = CarrionRng::new(20);
+        let mut party = Party::new(7, 1, LootRule::RoundRobin);
+        assert!(party.start_raid(1, &[30], &mut rng).is_err());
+        assert!(party.join(2).is_err());
+        assert!(party.invite(2, 3).is_err());
+        party.invite(1, 2).unwrap();
+        party.join(2).unwrap();
+        (3..=MAX_PARTY as u64).for_each(|user| party.invite(1, user).unwrap());
+        assert!(party.invite(1, 9).is_err());
+        assert!(party.start_raid(2, &[30, 30], &mut rng).is_err());
+
+        let mut boss = party.start_raid(1, &[30, 30], &mut rng).unwrap().clone();
+        boss.health = boss.health.min(200);
+        boss.items = vec![Default::default(), Default::default()];
+        let mut members: Vec<Character> = [Classes::Warrior, Classes::Wizard]
+            .into_iter()
+            .map(|class| {
+                let mut character = Character::new("Raider".into(), 1, class);
+                character.hp = 100_000;
+                character.max_hp = 100_000;
+                character
+            })
+            .collect();
+        let mut infos = raid(&mut members, &mut boss, &mut rng);
+        assert_eq!(infos.len(), 2);
+        assert!(!boss.alive());
+        assert!(infos.iter().all(|info| info.player_damage > 0));
+        assert_eq!(infos.iter().filter(|info| info.enemy_killed).count(), 1);
+
+        party.distribute(&boss, &mut infos, &mut rng);
+        assert!(infos.iter().all(|info| info.enemy_killed));
+        assert!(infos
+            .iter()
+            .all(|info| info.experience_gained == boss.experience));
+        assert_eq!(
+            infos.iter().map(|info| info.gold_gained).sum::<u64>(),
+            boss.gold
+        );
+        assert!(infos.iter().all(|info| info.item_gained.len() == 1));
+
+        assert!(!party.leave(1));
+        assert_eq!(party.leader, 2);
+        assert!(party.leave(2));
+    }
+}
@@ -0,0 +1,128 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use crate::item::{IndividualItem, Items};
+use crate::{CarrionError, CarrionResult};
+
+/// An open offer from one player to another. The item and gold are taken out of the sender's
+/// stash when the offer is made and sit in escrow in `GameData` until it is accepted, declined
+/// or cancelled. A player only has one open offer at a time.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Trade {
+    pub from: u64,
+    pub to: u64,
+    pub item: Option<IndividualItem>,
+    pub gold: u64,
+}
+
+impl Trade {
+    /// Moves the offered item and gold from `items` into the trade.
+    pub fn offer(
+        from: u64,
+        to: u64,
+        item: Option<&str>,
+        gold: u64,
+        items: &mut Items,
+    ) -> CarrionResult<Self> {
+        if from == to {
+            return Err(CarrionError::TradeError(
+                "You can not trade with yourself".to_string(),
+            ));
+        }
+        if item.is_none() && gold == 0 {
+            return Err(CarrionError::TradeError(
+                "Offer an item, gold or both".to_string(),
+            ));
+        }
+        if items.gold < gold {
+            return Err(CarrionError::TradeError(format!(
+                "You only have {} gold",
+                items.gold
+            )));
+        }
+        let item = match item {
+            Some(name) => match items.find(name).cloned() {
+                Some(item) => Some(item),
+                None => {
+                    return Err(CarrionError::TradeError(format!(
+                        "You have no {} in your items",
+                        name
+                    )))
+                }
+            },
+            None => None,
+        };
+
+        if let Some(item) = &item {
+            items.remove(item);
+        }
+        items.gold -= gold;
+        Ok(Self {
+            from,
+            to,
+            item,
+            gold,
+        })
+    }
+
+    /// Items are a set, so a trade can not hand over an item the receiver already has.
+    pub fn deliverable(&self, items: &Items) -> CarrionResult<()> {
+        match &self.item {
+            Some(item) if items.iter().any(|owned| owned == item) => Err(CarrionError::TradeError(
+                format!("{} is already in the items", item.name),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Hands what is in escrow to `items`, either the receiver's or the sender's on a decline.
+    pub fn deliver(self, items: &mut Items) -> CarrionResult<()> {
+        self.deliverable(items)?;
+        if let Some(item) = self.item {
+            items.stash(item);
+        }
+        items.gold += self.gold;
+        Ok(())
+    }
+}
+
+impl Display for Trade {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.item, self.gold) {
+            (Some(item), 0) => write!(f, "{}", item.name),
+            (Some(item), gold) => write!(f, "{} and {} gold", item.name, gold),
+            (None, gold) => write!(f, "{} gold", gold),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Trade;
+    use crate::{constructed::ItemsWeHave, item::Items};
+
+    #[test]
+    fn offers_hold_items_until_delivered() {
+        let staff = ItemsWeHave::InfernoStaff.generate();
+        let mut sender = Items::default();
+        sender.stash(staff.clone());
+        sender.gold = 50;
+        let mut receiver = Items::default();
+
+        assert!(Trade::offer(1, 1, Some("Inferno Staff"), 0, &mut sender).is_err());
+        assert!(Trade::offer(1, 2, Some("Inferno Staff"), 60, &mut sender).is_err());
+        assert!(Trade::offer(1, 2, Some("Nothing"), 0, &mut sender).is_err());
+
+        let trade = Trade::offer(1, 2, Some("inferno_staff"), 20, &mut sender).unwrap();
+        assert_eq!(sender.iter().count(), 0);
+        assert_eq!(sender.gold, 30);
+
+        receiver.stash(staff.clone());
+        assert!(trade.deliverable(&receiver).is_err());
+        receiver.remove(&staff);
+        trade.deliver(&mut receiver).unwrap();
+        assert_eq!(receiver.iter().collect::<Vec<_>>(), vec![&staff]);
+        assert_eq!(receiver.gold, 20);
+    }
+}
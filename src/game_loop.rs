@@ -68,10 +68,13 @@ async fn serenity_loop(tx: tokio::sync::mpsc::Sender<String>, character_id: u64)
             let results = get_game().await.battle(character_id).await;
 
             if results.result.is_empty() {
+                sleep(HEARTBEAT_INTERVAL).await;
+                mutations_task.abort();
                 continue;
             }
             let mut battle_info = String::from("```\n");
-            battle_info.push_str("Battle Results:\n");
+            battle_info.push_str(results.title());
+            battle_info.push('\n');
             for battle in results.result.iter() {
                 battle_info.push_str(&format!("{}\n", battle));
             }
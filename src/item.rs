@@ -2,18 +2,23 @@ use crate::character::Character;
 use crate::unit::Attributes;
 use crate::BattleInfo;
 use eris_macro::{ErisDisplayEmoji, ErisValidEnum};
+use heck::ToSnakeCase;
 use rand::distributions::Standard;
 use rand::prelude::Distribution;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 use std::collections::hash_set::Iter;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::{Display, Formatter};
 use std::fs;
 use std::hash::Hash;
 use std::ops::{Add, AddAssign};
+use strum::IntoEnumIterator;
 use tracing::{info, trace};
 
-use crate::damage::{DamageType, ResistCategories};
+use crate::constructed::{ItemSet, ItemsWeHave};
+use crate::damage::{DamageType, Defense, ResistCategories};
+use crate::items::GeneratedItem;
 
 #[derive(
     Debug, Clone, PartialEq, Serialize, Deserialize, Eq, Hash, ErisValidEnum, ErisDisplayEmoji,
@@ -183,6 +188,11 @@ impl Items {
         self.items.remove(item)
     }
 
+    /// Puts an item back into the stash without counting it as loot.
+    pub(crate) fn stash(&mut self, item: IndividualItem) -> bool {
+        self.items.insert(item)
+    }
+
     pub fn push(&mut self, item: IndividualItem) {
         match { self.items.insert(item.clone()) } {
             true => {
@@ -208,6 +218,14 @@ impl Items {
         }
     }
 
+    /// Finds an item by name the same way `ItemsWeHave` parses them, so "Inferno Staff" and
+    /// inferno_staff both work.
+    pub fn find(&self, name: &str) -> Option<&IndividualItem> {
+        self.items
+            .iter()
+            .find(|item| item.name.to_snake_case() == name.to_snake_case())
+    }
+
     pub fn take(&mut self, have_item: String) -> Option<IndividualItem> {
         let mut item_to_return = None;
         let item = self.items.iter().find(|item| item.name == have_item);
@@ -221,54 +239,43 @@ impl Items {
         item_to_return
     }
 
-    pub fn sell(&mut self, slot: Option<EquipmentSlot>) -> &mut Items {
-        if let Some(slot) = slot {
-            for item in &self.items {
-                if item.slot == slot {
-                    self.gold += item.rarity as u64;
-                }
-            }
-            self.items.retain(|item| item.slot != slot);
-        } else {
-            for item in &self.items {
-                self.gold += item.rarity as u64;
-            }
-            self.items.clear();
-        }
+    /// Sells every item of the slot, or everything, except the ones in `keep`.
+    pub fn sell(
+        &mut self,
+        slot: Option<EquipmentSlot>,
+        keep: &HashSet<IndividualItem>,
+    ) -> &mut Items {
+        self.sell_unkept(slot.as_ref(), keep);
         self
     }
 
+    fn sell_unkept(&mut self, slot: Option<&EquipmentSlot>, keep: &HashSet<IndividualItem>) {
+        let sold = |item: &IndividualItem| {
+            slot.into_iter().all(|slot| item.slot == *slot) && !keep.contains(item)
+        };
+        for item in self.items.iter().filter(|item| sold(item)) {
+            self.gold += item.rarity as u64;
+        }
+        self.items.retain(|item| !sold(item));
+    }
+
+    /// Like `sell`, but items that showed up after the player looked at `known_items` are kept.
     pub fn sell_with_knowledge(
         &mut self,
         slot: Option<&EquipmentSlot>,
         known_items: Option<&Items>,
+        keep: &HashSet<IndividualItem>,
     ) {
-        let known: Option<HashSet<_>> = known_items.map(|known| {
-            self.items
-                .drain()
-                .filter(|item| !known.items.contains(item))
-                .collect()
-        });
-
-        if let Some(slot) = slot {
-            for item in &self.items {
-                if item.slot == *slot {
-                    self.gold += item.rarity as u64;
-                }
-            }
-            self.items.retain(|item| item.slot != *slot);
-        } else {
-            for item in &self.items {
-                self.gold += item.rarity as u64;
-            }
-            self.items.clear();
-        }
-
-        if let Some(known) = known {
-            known.iter().for_each(|item| {
-                self.items.insert(item.clone());
-            });
+        let mut keep = keep.clone();
+        if let Some(known) = known_items {
+            keep.extend(
+                self.items
+                    .iter()
+                    .filter(|item| !known.items.contains(item))
+                    .cloned(),
+            );
         }
+        self.sell_unkept(slot, &keep);
     }
 }
 
@@ -522,11 +529,108 @@ impl Equipment {
         for wonder in &self.wondrous_item {
             item += wonder.item().unwrap_or(&IndividualItem::new()).clone();
         }
+        item += self.set_bonus();
         item.slot = EquipmentSlot::Inventory;
         item
     }
 
-    pub fn boost(&mut self, items: Items, character: Character) -> HashSet<IndividualItem> {
+    fn slots(&self) -> impl Iterator<Item = &NameMe> {
+        [
+            &self.helmet,
+            &self.armor,
+            &self.legs,
+            &self.feet,
+            &self.hands,
+            &self.weapon,
+            &self.shield,
+            &self.amulet,
+        ]
+        .into_iter()
+        .chain(self.ring.iter())
+        .chain(self.wondrous_item.iter())
+    }
+
+    fn slots_mut(&mut self) -> impl Iterator<Item = &mut NameMe> {
+        [
+            &mut self.helmet,
+            &mut self.armor,
+            &mut self.legs,
+            &mut self.feet,
+            &mut self.hands,
+            &mut self.weapon,
+            &mut self.shield,
+            &mut self.amulet,
+        ]
+        .into_iter()
+        .chain(self.ring.iter_mut())
+        .chain(self.wondrous_item.iter_mut())
+    }
+
+    pub fn items(&self) -> Vec<IndividualItem> {
+        self.slots()
+            .filter_map(|slot| slot.item().cloned())
+            .collect()
+    }
+
+    /// How many different pieces of every item set are equipped.
+    pub fn sets(&self) -> HashMap<ItemSet, usize> {
+        let mut pieces: HashMap<ItemSet, HashSet<&str>> = HashMap::new();
+        for item in self.slots().filter_map(|slot| slot.item()) {
+            if let Some(set) = item.set {
+                pieces.entry(set).or_default().insert(&item.name);
+            }
+        }
+        pieces
+            .into_iter()
+            .map(|(set, names)| (set, names.len()))
+            .collect()
+    }
+
+    /// Every active set bonus summed up like an item.
+    pub fn set_bonus(&self) -> IndividualItem {
+        let mut item = IndividualItem::new();
+        for (set, pieces) in self.sets() {
+            for bonus in set.bonuses() {
+                if bonus.pieces <= pieces {
+                    item += bonus.into();
+                }
+            }
+        }
+        item
+    }
+
+    /// Empties every slot whose item `owned` can't provide and returns those items.
+    pub(crate) fn keep_owned(
+        &mut self,
+        mut owned: impl FnMut(&IndividualItem) -> bool,
+    ) -> Vec<IndividualItem> {
+        let mut missing = vec![];
+        for slot in self.slots_mut() {
+            match slot.item.take() {
+                Some(item) if owned(&item) => slot.item = Some(item),
+                Some(item) => {
+                    slot.player_equipped = false;
+                    missing.push(item);
+                }
+                None => {}
+            }
+        }
+        missing
+    }
+
+    /// Pins every equipped item against `auto_equip`.
+    fn pin(&mut self) {
+        for slot in self.slots_mut() {
+            slot.player_equipped = slot.item.is_some();
+        }
+    }
+
+    pub fn boost(
+        &mut self,
+        items: Items,
+        character: Character,
+        rng: &mut impl Rng,
+    ) -> HashSet<IndividualItem> {
         let items_to_return: HashSet<IndividualItem> = items
             .iter()
             .filter(|item| item.rarity <= Rarity::Artifact)
@@ -536,55 +640,55 @@ impl Equipment {
             match item.slot {
                 EquipmentSlot::Helmet => {
                     if let Some(helmet) = self.helmet.item.as_mut() {
-                        helmet.boost(item, &character);
+                        helmet.boost(item, &character, rng);
                     }
                 }
                 EquipmentSlot::Armor => {
                     if let Some(armor) = self.armor.item.as_mut() {
-                        armor.boost(item, &character);
+                        armor.boost(item, &character, rng);
                     }
                 }
                 EquipmentSlot::Legs => {
                     if let Some(legs) = self.legs.item.as_mut() {
-                        legs.boost(item, &character);
+                        legs.boost(item, &character, rng);
                     }
                 }
                 EquipmentSlot::Feet => {
                     if let Some(feet) = self.feet.item.as_mut() {
-                        feet.boost(item, &character);
+                        feet.boost(item, &character, rng);
                     }
                 }
                 EquipmentSlot::Hands => {
                     if let Some(hands) = self.hands.item.as_mut() {
-                        hands.boost(item, &character);
+                        hands.boost(item, &character, rng);
                     }
                 }
                 EquipmentSlot::Weapon => {
                     if let Some(weapon) = self.weapon.item.as_mut() {
-                        weapon.boost(item, &character);
+                        weapon.boost(item, &character, rng);
                     }
                 }
                 EquipmentSlot::Shield => {
                     if let Some(shield) = self.shield.item.as_mut() {
-                        shield.boost(item, &character);
+                        shield.boost(item, &character, rng);
                     }
                 }
                 EquipmentSlot::Ring => {
                     for ring in &mut self.ring {
                         if let Some(ring) = ring.item.as_mut() {
-                            ring.boost(item.clone(), &character);
+                            ring.boost(item.clone(), &character, rng);
                         }
                     }
                 }
                 EquipmentSlot::Amulet => {
                     if let Some(amulet) = self.amulet.item.as_mut() {
-                        amulet.boost(item, &character);
+                        amulet.boost(item, &character, rng);
                     }
                 }
                 EquipmentSlot::WondrousItem => {
                     for wonder in &mut self.wondrous_item {
                         if let Some(wonder) = wonder.item.as_mut() {
-                            wonder.boost(item.clone(), &character);
+                            wonder.boost(item.clone(), &character, rng);
                         }
                     }
                 }
@@ -700,6 +804,7 @@ impl Equipment {
             acc += item.dodge();
             acc
         });
+        base += self.set_bonus().dodge;
         base
     }
     pub fn armor(&self) -> i32 {
@@ -721,6 +826,7 @@ impl Equipment {
             acc += item.armor();
             acc
         });
+        base += self.set_bonus().armor;
         base
     }
     pub fn resistance(&self) -> HashMap<ResistCategories, i32> {
@@ -768,6 +874,10 @@ impl Equipment {
                 .iter()
                 .for_each(|(k, v)| *resistance.get_mut(k).unwrap() += v);
         }
+        self.set_bonus()
+            .resistance
+            .iter()
+            .for_each(|(k, v)| *resistance.get_mut(k).unwrap() += v);
         resistance
     }
 
@@ -814,6 +924,7 @@ impl Equipment {
             acc += item.action();
             acc
         });
+        base += self.set_bonus().action;
 
         base
     }
@@ -858,6 +969,10 @@ impl Equipment {
                 .iter()
                 .for_each(|(k, v)| *base.get_mut(k).unwrap() += v);
         }
+        self.set_bonus()
+            .damage
+            .iter()
+            .for_each(|(k, v)| *base.get_mut(k).unwrap() += v);
 
         base
     }
@@ -870,6 +985,12 @@ impl Equipment {
         vec
     }
 
+    pub fn find(&self, name: &str) -> Option<IndividualItem> {
+        self.items()
+            .into_iter()
+            .find(|item| item.name.to_snake_case() == name.to_snake_case())
+    }
+
     pub(crate) fn get(&self, slot: EquipmentSlot) -> Vec<IndividualItem> {
         let mut vec = Vec::new();
         match slot {
@@ -1033,10 +1154,252 @@ impl Display for Equipment {
             }
         }
 
+        for (set, pieces) in self.sets() {
+            string.push_str("\n\t");
+            string.push_str("🎲\t");
+            string.push_str(&format!("Set: {} ({} pieces)", set.name(), pieces));
+        }
+
+        write!(f, "{}", string)
+    }
+}
+
+/// Granted by an `ItemSet` once `pieces` different items of the set are equipped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetBonus {
+    pub pieces: usize,
+    pub armor: i32,
+    pub dodge: i32,
+    pub resistance: HashMap<ResistCategories, i32>,
+    pub damage: HashMap<DamageType, i32>,
+    pub action: i32,
+}
+
+impl From<SetBonus> for IndividualItem {
+    fn from(bonus: SetBonus) -> Self {
+        let mut item = IndividualItem::new();
+        item.armor = bonus.armor;
+        item.dodge = bonus.dodge;
+        item.action = bonus.action;
+        item.resistance.extend(bonus.resistance);
+        item.damage.extend(bonus.damage);
+        item
+    }
+}
+
+/// Named snapshots of `Equipment` to swap between, like a "boss" and a "farm" set of gear.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Loadouts {
+    active: Option<String>,
+    saved: BTreeMap<String, Equipment>,
+}
+
+impl Loadouts {
+    /// Saves the equipment with every item pinned against `auto_equip`.
+    pub fn save(&mut self, name: String, equipment: &Equipment) {
+        let mut equipment = equipment.clone();
+        equipment.pin();
+        self.saved.insert(name.clone(), equipment);
+        self.active = Some(name);
+    }
+
+    pub fn remove(&mut self, name: &str) -> bool {
+        if self.active.as_deref() == Some(name) {
+            self.active = None;
+        }
+        self.saved.remove(name).is_some()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Equipment> {
+        self.saved.get(name)
+    }
+
+    pub fn active(&self) -> Option<&String> {
+        self.active.as_ref()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &String> {
+        self.saved.keys()
+    }
+
+    /// Every item kept in a loadout, these never get sold.
+    pub fn items(&self) -> HashSet<IndividualItem> {
+        self.saved.values().flat_map(Equipment::items).collect()
+    }
+
+    /// Swaps `equipment` for the saved loadout. The current gear goes into `items`, the active
+    /// loadout is updated first so boosted items are not lost. Pieces that are no longer owned
+    /// are left empty and returned, `None` if there is no such loadout.
+    pub fn swap(
+        &mut self,
+        name: &str,
+        equipment: &mut Equipment,
+        items: &mut Items,
+    ) -> Option<Vec<IndividualItem>> {
+        if !self.saved.contains_key(name) {
+            return None;
+        }
+        if let Some(active) = &self.active {
+            self.saved.insert(active.clone(), equipment.clone());
+        }
+        let mut loadout = self.saved[name].clone();
+        for item in equipment.items() {
+            items.stash(item);
+        }
+        let missing = loadout.keep_owned(|item| items.remove(item));
+        *equipment = loadout;
+        self.saved.insert(name.to_string(), equipment.clone());
+        self.active = Some(name.to_string());
+        Some(missing)
+    }
+}
+
+impl Display for Loadouts {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut string = String::new();
+        for (name, equipment) in &self.saved {
+            string.push('\n');
+            string.push_str(name);
+            if self.active.as_ref() == Some(name) {
+                string.push_str(" (active)");
+            }
+            string.push_str(&equipment.to_string());
+        }
         write!(f, "{}", string)
     }
 }
 
+/// An item next to what equipping it would change. It is equipped on a copy of the character,
+/// so set bonuses and the ring / wondrous item rotation are taken into account.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemComparison {
+    pub item: IndividualItem,
+    pub replaced: Option<IndividualItem>,
+    before: IndividualItem,
+    after: IndividualItem,
+    mitigation: Vec<(ResistCategories, f64, f64)>,
+    dodge: (f64, f64),
+}
+
+impl ItemComparison {
+    pub fn new(item: IndividualItem, character: &Character) -> Self {
+        let mut equipped = character.clone();
+        let replaced = match character.equipment.items().contains(&item) {
+            true => Some(item.clone()),
+            false => equipped.equipment.equip(item.clone()),
+        };
+        let (old, new) = (Defense::new(character), Defense::new(&equipped));
+        let mitigation = ResistCategories::iter()
+            .map(|resist| (resist, old.defense(resist), new.defense(resist)))
+            .collect();
+        Self {
+            item,
+            replaced,
+            before: character.equipment.sum(),
+            after: equipped.equipment.sum(),
+            mitigation,
+            dodge: (old.dodge_chance(), new.dodge_chance()),
+        }
+    }
+
+    /// Looks for the item in the stash, then in the equipment and falls back to the base item.
+    pub fn find(name: &str, character: &Character, items: &Items) -> Option<Self> {
+        let item = items
+            .find(name)
+            .cloned()
+            .or_else(|| character.equipment.find(name))
+            .or_else(|| {
+                ItemsWeHave::try_from(name.to_string())
+                    .ok()
+                    .map(|item| item.generate())
+            })?;
+        Some(Self::new(item, character))
+    }
+
+    pub fn cli_display(&self) -> Vec<String> {
+        let mut lines = self.item.cli_display();
+        match &self.replaced {
+            Some(item) if *item == self.item => {
+                lines.push("Currently equipped".to_string());
+                return lines;
+            }
+            Some(item) => lines.push(format!("Compared to {}:", item.name)),
+            None => lines.push(format!("Compared to an empty {:?} slot:", self.item.slot)),
+        }
+
+        let (before, after) = (&self.before, &self.after);
+        let mut changes = vec![
+            change("Armor", before.armor, after.armor),
+            change("Dodge", before.dodge, after.dodge),
+            change("Action", before.action, after.action),
+        ];
+        for attribute in [
+            "Strength",
+            "Dexterity",
+            "Constitution",
+            "Intelligence",
+            "Wisdom",
+            "Charisma",
+        ] {
+            changes.push(change(
+                attribute,
+                before.attribute_bonus.get(attribute),
+                after.attribute_bonus.get(attribute),
+            ));
+        }
+        for dtype in DamageType::iter() {
+            let get = |item: &IndividualItem| *item.damage.get(&dtype).unwrap_or(&0);
+            changes.push(change(
+                &format!("{:?} damage", dtype),
+                get(before),
+                get(after),
+            ));
+        }
+        for resist in ResistCategories::iter() {
+            let get = |item: &IndividualItem| *item.resistance.get(&resist).unwrap_or(&0);
+            changes.push(change(
+                &format!("{:?} resistance", resist),
+                get(before),
+                get(after),
+            ));
+        }
+        for (resist, old, new) in &self.mitigation {
+            changes.push(change_percent(
+                &format!("{:?} mitigation", resist),
+                *old,
+                *new,
+            ));
+        }
+        changes.push(change_percent("Dodge chance", self.dodge.0, self.dodge.1));
+
+        let changes: Vec<String> = changes.into_iter().flatten().collect();
+        if changes.is_empty() {
+            lines.push("\tNo change".to_string());
+        }
+        lines.extend(changes);
+        lines
+    }
+}
+
+fn change(stat: &str, before: i32, after: i32) -> Option<String> {
+    (before != after).then(|| format!("\t{stat}: {before} -> {after} ({:+})", after - before))
+}
+
+fn change_percent(stat: &str, before: f64, after: f64) -> Option<String> {
+    ((before - after).abs() >= 0.05).then(|| {
+        format!(
+            "\t{stat}: {before:.1}% -> {after:.1}% ({:+.1}%)",
+            after - before
+        )
+    })
+}
+
+impl Display for ItemComparison {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "\n```\n{}\n```\n", self.cli_display().join("\n"))
+    }
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
 pub struct IndividualItem {
     pub name: String,
@@ -1050,6 +1413,8 @@ pub struct IndividualItem {
     pub rarity: Rarity,
     pub action: i32,
     pub points: u64,
+    #[serde(default)]
+    pub set: Option<ItemSet>,
 }
 
 impl Display for IndividualItem {
@@ -1123,12 +1488,17 @@ impl IndividualItem {
             rarity: Rarity::Common,
             action: 0,
             points: 0,
+            set: None,
         }
     }
     pub fn cli_display(&self) -> Vec<String> {
         let mut string_vec = Vec::new();
         string_vec.push(format!("Name: {}", self.name));
         string_vec.push(format!("Description: {}", self.description));
+        string_vec.push(format!("Rarity: {:?}", self.rarity));
+        if let Some(set) = self.set {
+            string_vec.push(format!("Set: {}", set.name()));
+        }
         if self.armor != 0 {
             string_vec.push(format!("Armor: {}", self.armor));
         }
@@ -1200,7 +1570,7 @@ impl IndividualItem {
         }
     }
 
-    pub fn boost(&mut self, sacrifice: IndividualItem, character: &Character) {
+    pub fn boost(&mut self, sacrifice: IndividualItem, character: &Character, rng: &mut impl Rng) {
         if self.slot != sacrifice.slot {
             info!("Cannot boost item of different slot");
             return;
@@ -1226,8 +1596,9 @@ impl IndividualItem {
                 "Cannot boost item without element scaling. This is a bug. Please report it.",
             );
 
-        let new_sacrifice: IndividualItem =
-            (element_scaling, attribute_scaling, sacrifice_points).into();
+        let new_sacrifice =
+            GeneratedItem::scaled(element_scaling, attribute_scaling, sacrifice_points, rng)
+                .item(rng);
         *self += new_sacrifice;
         self.update_name();
     }
@@ -1256,16 +1627,21 @@ impl Hash for IndividualItem {
         self.attribute_bonus.hash(state);
         self.rarity.hash(state);
         self.action.hash(state);
+        self.set.hash(state);
     }
 }
 
 #[cfg(test)]
 mod test {
 
+    use std::collections::HashSet;
+
     use crate::{
         character::Character,
+        class::Classes,
+        constructed::ItemsWeHave,
         damage::{DamageType, ResistCategories},
-        item::{EquipmentSlot, IndividualItem, Rarity},
+        item::{Equipment, EquipmentSlot, IndividualItem, ItemComparison, Items, Loadouts, Rarity},
         unit::Attributes,
     };
 
@@ -1283,6 +1659,7 @@ mod test {
             rarity: Rarity::Common,
             action: 1,
             points: 1,
+            set: None,
         };
 
         item1.damage.insert(DamageType::Elemental, 3);
@@ -1298,6 +1675,7 @@ mod test {
             rarity: Rarity::Common,
             action: 1,
             points: 1,
+            set: None,
         };
         item2.damage.insert(DamageType::Elemental, 3);
         let mut item3 = IndividualItem {
@@ -1312,6 +1690,7 @@ mod test {
             rarity: Rarity::Common,
             action: 1,
             points: 1,
+            set: None,
         };
         item3.damage.insert(DamageType::Elemental, 3);
         item3 += item1;
@@ -1337,6 +1716,7 @@ mod test {
             rarity: Rarity::Common,
             action: 1,
             points: 1,
+            set: None,
         };
         let sacrifice = IndividualItem {
             name: "Test Item".to_string(),
@@ -1350,11 +1730,12 @@ mod test {
             rarity: Rarity::Common,
             action: 1,
             points: 200000,
+            set: None,
         };
         let character: Character = Default::default();
 
         let old_item = item.clone();
-        item.boost(sacrifice, &character);
+        item.boost(sacrifice, &character, &mut crate::rng::CarrionRng::new(14));
         item.name = old_item.name.clone();
         println!("Boosted {:?}", item);
         println!("\n");
@@ -1362,4 +1743,74 @@ mod test {
         println!("\n");
         assert_ne!(item, old_item)
     }
+
+    #[test]
+    fn set_bonuses_need_enough_pieces() {
+        let mut equipment = Equipment::default();
+        equipment.equip(ItemsWeHave::AsgardianAegis.generate());
+        let armor = equipment.armor();
+        assert_eq!(equipment.set_bonus().armor, 0);
+
+        equipment.equip(ItemsWeHave::LegguardsOfInvincibleAegis.generate());
+        assert!(equipment.set_bonus().armor > 0);
+        assert_eq!(
+            equipment.armor(),
+            armor
+                + ItemsWeHave::LegguardsOfInvincibleAegis.generate().armor
+                + equipment.set_bonus().armor
+        );
+        assert_eq!(equipment.sum().armor, equipment.armor());
+    }
+
+    #[test]
+    fn loadouts_swap_gear_and_keep_it_from_being_sold() {
+        let staff = ItemsWeHave::InfernoStaff.generate();
+        let sword = ItemsWeHave::SwordOfAsbethathTheBetrayer.generate();
+        let mut equipment = Equipment::default();
+        let mut items = Items::default();
+        let mut loadouts = Loadouts::default();
+
+        equipment.equip(staff.clone());
+        loadouts.save("farm".to_string(), &equipment);
+        items.stash(equipment.equip(sword.clone()).unwrap());
+        loadouts.save("boss".to_string(), &equipment);
+
+        assert_eq!(
+            loadouts.swap("farm", &mut equipment, &mut items),
+            Some(vec![])
+        );
+        assert_eq!(equipment.items(), vec![staff.clone()]);
+        assert_eq!(items.iter().collect::<Vec<_>>(), vec![&sword]);
+
+        items.sell(None, &loadouts.items());
+        assert_eq!(items.iter().count(), 1);
+        loadouts.remove("boss");
+        items.sell(None, &HashSet::new());
+        assert_eq!(items.iter().count(), 0);
+
+        assert_eq!(loadouts.swap("boss", &mut equipment, &mut items), None);
+    }
+
+    #[test]
+    fn inspect_compares_against_the_equipped_item() {
+        let mut character = Character::new("Inspector".into(), 1, Classes::Warrior);
+        character
+            .equipment
+            .equip(ItemsWeHave::AsgardianAegis.generate());
+        let items = Items::default();
+
+        let legguards =
+            ItemComparison::find("Legguards of Invincible Aegis", &character, &items).unwrap();
+        assert_eq!(legguards.replaced, None);
+        let lines = legguards.cli_display();
+        assert!(lines.iter().any(|line| line.contains("Armor:")));
+        assert!(lines
+            .iter()
+            .any(|line| line.contains("Physical mitigation")));
+
+        let aegis = ItemComparison::find("asgardian_aegis", &character, &items).unwrap();
+        assert_eq!(aegis.replaced.as_ref(), Some(&aegis.item));
+        assert_eq!(aegis.cli_display().last().unwrap(), "Currently equipped");
+        assert!(ItemComparison::find("Not An Item", &character, &items).is_none());
+    }
 }
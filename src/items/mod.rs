@@ -10,7 +10,7 @@ use crate::{
     EnemyEvents,
 };
 
-use rand::{prelude::Distribution, random, thread_rng, Rng};
+use rand::{prelude::Distribution, seq::SliceRandom, Rng};
 use random_word::Lang;
 use serde::{Deserialize, Serialize};
 use strum::{EnumIter, IntoEnumIterator};
@@ -75,7 +75,7 @@ impl From<String> for AttributeChoices {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
-struct GeneratedItem {
+pub(crate) struct GeneratedItem {
     points: u64,
     dodge: u64,
     armor: u64,
@@ -108,9 +108,25 @@ impl GeneratedItem {
         self.points.into()
     }
 
-    fn distribute_points(&mut self, element: Option<DamageType>, attribute: Option<String>) {
+    /// Spreads `points` with a bias towards `damage_type` and `attribute`.
+    pub(crate) fn scaled(
+        damage_type: DamageType,
+        attribute: String,
+        points: u64,
+        rng: &mut impl Rng,
+    ) -> Self {
+        let mut base = Self::new(points);
+        base.distribute_points(Some(damage_type), Some(attribute), rng);
+        base
+    }
+
+    fn distribute_points(
+        &mut self,
+        element: Option<DamageType>,
+        attribute: Option<String>,
+        rng: &mut impl Rng,
+    ) {
         self.rarity = self.rarity();
-        let mut rng = thread_rng();
         let mut points = rng.gen_range(self.points / 2..self.points);
         self.points = points;
         let mut action_rolls = self.points / 3750;
@@ -130,7 +146,7 @@ impl GeneratedItem {
                 if let Some(ref attr) = attribute {
                     self.attribute_bonus.add(attr, 1);
                 } else {
-                    let chosen: AttributeChoices = random();
+                    let chosen: AttributeChoices = rng.gen();
                     match chosen {
                         AttributeChoices::Strength => {
                             self.attribute_bonus.strength += 1;
@@ -163,7 +179,7 @@ impl GeneratedItem {
         let sum_scaling = (damage_scaling + resist_scaling) * 5;
 
         while points > 0 {
-            let choice: Choices = random();
+            let choice: Choices = rng.gen();
             let points_cost: u64;
             match choice {
                 Choices::Armor => {
@@ -181,7 +197,7 @@ impl GeneratedItem {
                 Choices::Resistance => {
                     let scale = (points / sum_scaling as u64).max(1) * resist_scaling as u64;
                     let resistance = rng.gen_range(0..scale).max(1);
-                    let resist: ResistCategories = random();
+                    let resist: ResistCategories = rng.gen();
                     self.resistance.insert(resist, resistance as i32);
                     points_cost = resistance;
                 }
@@ -191,7 +207,7 @@ impl GeneratedItem {
                     if let Some(ele) = element {
                         set_element = ele;
                     } else {
-                        set_element = random();
+                        set_element = rng.gen();
                     }
                     let damage = rng.gen_range(0..scale).max(1);
                     self.damage.insert(set_element, damage as i32);
@@ -202,12 +218,12 @@ impl GeneratedItem {
         }
     }
 
-    fn slot(&self) -> EquipmentSlot {
-        random()
+    fn slot(&self, rng: &mut impl Rng) -> EquipmentSlot {
+        rng.gen()
     }
 
-    pub fn item(self) -> IndividualItem {
-        let slot = self.slot();
+    pub fn item(self, rng: &mut impl Rng) -> IndividualItem {
+        let slot = self.slot(rng);
         let rarity = self.rarity;
         let mut stat = String::new();
         if let Some(attribute) = self.attribute_bonus.max_stat() {
@@ -237,7 +253,9 @@ impl GeneratedItem {
         let mut name = String::new();
         name += action_string;
         name += format!("{rarity:?} ").as_str();
-        name += random_word::gen(Lang::En);
+        name += random_word::all(Lang::En)
+            .choose(rng)
+            .expect("Word list is empty");
         name += format!(" {slot:?} ").as_str();
         name += stat.as_str();
         IndividualItem {
@@ -252,79 +270,51 @@ impl GeneratedItem {
             action: self.action as i32,
             rarity,
             points: self.points,
+            set: None,
         }
     }
 }
 
-impl From<u64> for GeneratedItem {
-    fn from(points: u64) -> Self {
-        let mut base = Self::new(points);
-        base.distribute_points(None, None);
-        base
-    }
-}
-
-impl From<Rarity> for GeneratedItem {
-    fn from(rarity: Rarity) -> Self {
-        // GeneratedItem can not be more rare than Legendary
-        if rarity < Rarity::Epic {
-            let points = Rarity::Epic.item_points();
-            Self::from(points)
-        } else {
-            let points = rarity.item_points();
-            Self::from(points)
-        }
-    }
-}
-
-impl From<&Enemy> for Vec<IndividualItem> {
-    fn from(enemy: &Enemy) -> Self {
-        let grade = enemy.kind.grade();
-        let level = enemy.level;
-        // let probabilty_of_drop = (grade as u32) as f64 / 200.0;
-        let probabilty_of_drop = 0.99;
-        if thread_rng().gen_bool(probabilty_of_drop) {
-            let points_range = (grade as u64)..(grade as u64 * level as u64 * 2).max(1000);
-            let points = thread_rng()
-                .gen_range(points_range)
-                .min(Rarity::Epic.item_points());
-            vec![GeneratedItem::from(points).item()]
-        } else {
-            vec![]
-        }
-    }
-}
-
-impl From<(DamageType, String, u64)> for GeneratedItem {
-    fn from((damage_type, attribute, points): (DamageType, String, u64)) -> Self {
-        let mut base = Self::new(points);
-        base.distribute_points(Some(damage_type), Some(attribute));
-        base
+pub(crate) fn drops(enemy: &Enemy, rng: &mut impl Rng) -> Vec<IndividualItem> {
+    let grade = enemy.kind.grade();
+    let level = enemy.level;
+    // let probabilty_of_drop = (grade as u32) as f64 / 200.0;
+    let probabilty_of_drop = 0.99;
+    if rng.gen_bool(probabilty_of_drop) {
+        let points_range = (grade as u64)..(grade as u64 * level as u64 * 2).max(1000);
+        let points = rng.gen_range(points_range).min(Rarity::Epic.item_points());
+        let mut generated = GeneratedItem::new(points);
+        generated.distribute_points(None, None, rng);
+        vec![generated.item(rng)]
+    } else {
+        vec![]
     }
 }
 
-impl From<(DamageType, String, u64)> for IndividualItem {
-    fn from((damage_type, name, points): (DamageType, String, u64)) -> Self {
-        let generated: GeneratedItem = (damage_type, name, points).into();
-        generated.item()
-    }
+/// Generates an item of the given rarity in a random slot, for the shop instead of a drop.
+pub(crate) fn generate(rarity: Rarity, rng: &mut impl Rng) -> IndividualItem {
+    let points = rarity.item_points().min(Rarity::Epic.item_points());
+    let mut generated = GeneratedItem::new(points);
+    generated.distribute_points(None, None, rng);
+    generated.item(rng)
 }
 
 #[cfg(test)]
 mod test {
 
-    use crate::{enemy::Mob, item::IndividualItem};
+    use crate::{enemy::Mob, item::IndividualItem, rng::CarrionRng};
 
-    use super::GeneratedItem;
+    use super::{drops, generate};
 
     #[test]
     fn item_from_level_30_legendary_enemy() {
+        let mut rng = CarrionRng::new(3);
         for _i in 0..1000 {
             let mut character = crate::character::Character::default();
             character.level = 30;
             let enemy = Mob::Eldragor;
-            let enemy = enemy.generate(character.level);
-            let item: Vec<IndividualItem> = (&enemy).into();
+            let enemy = enemy.generate(character.level, &mut rng);
+            let item: Vec<IndividualItem> = drops(&enemy, &mut rng);
             for i in item.iter() {
                 assert!(i.points >= 1, "Points is too low {}", i.points);
                 assert!(i.points <= 1600 * 2, "Points is too high {}", i.points);
@@ -333,12 +323,13 @@ mod test {
     }
     #[test]
     fn item_from_level_60_legendary_enemy() {
+        let mut rng = CarrionRng::new(4);
         for _i in 0..1000 {
             let mut character = crate::character::Character::default();
             character.level = 60;
             let enemy = Mob::Eldragor;
-            let enemy = enemy.generate(character.level);
-            let item: Vec<IndividualItem> = (&enemy).into();
+            let enemy = enemy.generate(character.level, &mut rng);
+            let item: Vec<IndividualItem> = drops(&enemy, &mut rng);
             for i in item.iter() {
                 assert!(i.points >= 1, "Points is too low {}", i.points);
                 assert!(i.points <= 15000, "Points is too high {}", i.points);
@@ -349,9 +340,9 @@ mod test {
     #[test]
     fn item_generation_range_for_rate() {
         let rarity = crate::item::Rarity::Rare;
+        let mut rng = CarrionRng::new(5);
         for n in 0..10000 {
-            let item: GeneratedItem = rarity.into();
-            let item = item.item();
+            let item = generate(rarity, &mut rng);
             assert!(
                 item.points >= rarity.item_points() / 2,
                 "Points is too low {item:?} iteration {n}"
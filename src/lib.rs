@@ -1,10 +1,14 @@
 pub mod battle;
+pub mod buff;
 pub mod class;
+pub mod combat;
 pub mod command;
 pub mod items;
 #[rustfmt::skip]
 pub mod constructed;
 pub mod database;
+pub mod dungeon;
+pub mod encounter;
 
 pub mod character;
 pub mod damage;
@@ -12,10 +16,17 @@ pub mod enemy;
 pub mod game;
 pub mod game_loop;
 pub mod item;
+pub mod replay;
+pub mod rng;
+pub mod rotation;
+pub mod shop;
+pub mod simulation;
 pub mod skill;
+pub mod status;
 pub mod r#trait;
 pub mod ui;
 pub mod unit;
+pub mod zone;
 
 // Custom user data passed to all command functions
 #[derive(Debug)]
@@ -32,7 +43,10 @@ use tracing::{instrument, Level};
 use surrealdb::sql::Thing;
 use thiserror::Error;
 
+use buff::Buff;
+use encounter::EnemyReport;
 use skill::Skill;
+use status::StatusTick;
 
 use crate::character::Character;
 use crate::enemy::{Enemy, Mob};
@@ -50,6 +64,28 @@ pub enum CarrionError {
     SurrealError(#[from] surrealdb::Error),
     #[error("Parse error: {0}")]
     ParseError(String),
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("Serde error: {0}")]
+    SerdeError(#[from] serde_json::Error),
+    #[error("Replay error: {0}")]
+    ReplayError(String),
+    #[error("{0}")]
+    ShopError(String),
+    #[error("{0}")]
+    TradeError(String),
+    #[error("{0}")]
+    DungeonError(String),
+    #[error("{0}")]
+    PromotionError(String),
+    #[error("{0}")]
+    MulticlassError(String),
+    #[error("{0}")]
+    RotationError(String),
+    #[error("{0}")]
+    PartyError(String),
+    #[error("{0}")]
+    TraitError(String),
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -77,6 +113,9 @@ pub struct BattleInfo {
     pub player_killed: bool,
     pub enemy_killed: bool,
     pub critical: bool,
+    /// A hit landed on the enemy's vulnerability.
+    #[serde(default)]
+    pub super_effective: bool,
     pub leveled_up: bool,
     pub monster_hp: i32,
     pub enemy_level: u32,
@@ -89,10 +128,26 @@ pub struct BattleInfo {
     pub number_of_player_attacks: i32,
     pub number_of_enemy_attacks: i32,
     pub custom_text: Option<String>,
+    pub rounds: u32,
+    /// Every status effect tick, with the round it happened in.
+    pub status_ticks: Vec<(u32, StatusTick)>,
+    /// Every buff the player cast, with the round it was cast in.
+    #[serde(default)]
+    pub buffs: Vec<(u32, Buff)>,
+    /// Skill experience per skill used, `skill_experience_gained` is the total.
+    #[serde(default)]
+    pub skill_experience: Vec<(Skill, u64)>,
+    /// How every enemy of the encounter fared, in the order they were met.
+    #[serde(default)]
+    pub enemies: Vec<EnemyReport>,
+    /// Ascended skills the skill experience of this battle unlocked.
+    #[serde(default)]
+    pub discovered: Vec<Skill>,
 }
 
 impl BattleInfo {
-    pub fn begin(character: &Character, enemy: &Enemy) -> Self {
+    pub fn begin(character: &Character, enemies: &[Enemy]) -> Self {
+        let lead = enemies.first().expect("An encounter has no enemies");
         Self {
             action: character.current_skill.skill(),
             enemy_action: "".to_string(),
@@ -101,12 +156,13 @@ impl BattleInfo {
             player_healing: 0,
             enemy_healing: 0,
             player_name: character.name.clone(),
-            monster_name: enemy.kind.to_string(),
+            monster_name: encounter::describe(enemies),
             player_killed: false,
             enemy_killed: false,
             critical: false,
+            super_effective: false,
             leveled_up: false,
-            monster_hp: enemy.health,
+            monster_hp: lead.health,
             traits_available: character.available_traits,
             next_level: character.experience_to_next_level(),
             experience_gained: 0,
@@ -116,10 +172,61 @@ impl BattleInfo {
             number_of_player_attacks: 0,
             number_of_enemy_attacks: 0,
             custom_text: None,
-            enemy_level: enemy.level,
+            enemy_level: enemies
+                .iter()
+                .map(|enemy| enemy.level)
+                .max()
+                .unwrap_or(lead.level),
             enemy_healing_action: "".to_string(),
+            rounds: 0,
+            status_ticks: vec![],
+            buffs: vec![],
+            skill_experience: vec![],
+            enemies: enemies.iter().map(EnemyReport::from).collect(),
+            discovered: vec![],
         }
     }
+
+    /// Records what just happened to the enemy at `index` of the encounter.
+    pub(crate) fn update_enemy(&mut self, index: usize, enemy: &Enemy, damage: i32, healing: i32) {
+        self.monster_hp = enemy.health;
+        if let Some(report) = self.enemies.get_mut(index) {
+            report.hp = enemy.health;
+            report.damage_taken += damage;
+            report.healing += healing;
+            report.killed = !enemy.alive();
+        }
+    }
+
+    pub(crate) fn gain_skill_experience(&mut self, skill: Skill, experience: u64) {
+        self.skill_experience_gained += experience;
+        match self.skill_experience.iter_mut().find(|(s, _)| *s == skill) {
+            Some((_, gained)) => *gained += experience,
+            None => self.skill_experience.push((skill, experience)),
+        }
+    }
+
+    /// Sums the status ticks per combatant and effect into (target, effect, turns, amount).
+    pub fn status_totals(&self) -> Vec<(String, damage::UniqueDamageEffect, u32, i32)> {
+        let mut totals: Vec<(String, damage::UniqueDamageEffect, u32, i32)> = vec![];
+        for (_, tick) in &self.status_ticks {
+            let target = match tick.target {
+                replay::Combatant::Player => self.player_name.clone(),
+                replay::Combatant::Enemy => self.monster_name.clone(),
+            };
+            match totals
+                .iter_mut()
+                .find(|(t, effect, _, _)| *t == target && *effect == tick.status.effect)
+            {
+                Some(total) => {
+                    total.2 += 1;
+                    total.3 += tick.amount;
+                }
+                None => totals.push((target, tick.status.effect, 1, tick.amount)),
+            }
+        }
+        totals
+    }
 }
 
 impl Display for BattleInfo {
@@ -178,6 +285,49 @@ impl Display for BattleInfo {
             string.push_str("\n\t");
         }
 
+        for (round, buff) in &self.buffs {
+            string.push_str("✨\t");
+            string.push_str(&format!(
+                "{} cast {} in round {round}",
+                self.player_name, buff
+            ));
+            string.push_str("\t✨");
+            string.push_str("\n\t");
+        }
+
+        for skill in &self.discovered {
+            string.push_str("🌠\t");
+            string.push_str(&format!(
+                "{} discovered the ascended skill {}!",
+                self.player_name, skill
+            ));
+            string.push_str("\t🌠");
+            string.push_str("\n\t");
+        }
+
+        for (target, effect, turns, amount) in self.status_totals() {
+            string.push_str("🧪\t");
+            string.push_str(&format!("{target} {effect:?} for {turns} turns: {amount}"));
+            string.push_str("\t🧪");
+            string.push_str("\n\t");
+        }
+
+        if self.enemies.len() > 1 {
+            for enemy in &self.enemies {
+                string.push_str("⚔️\t");
+                string.push_str(&format!(
+                    "{} (level {}) took {} damage",
+                    enemy.name, enemy.level, enemy.damage_taken
+                ));
+                match enemy.killed {
+                    true => string.push_str(", killed"),
+                    false => string.push_str(&format!(", {} HP left", enemy.hp)),
+                }
+                string.push_str("\t⚔️");
+                string.push_str("\n\t");
+            }
+        }
+
         string.push_str("🪨\t");
         string.push_str("Next level in ");
         string.push_str(&self.next_level.to_string());
@@ -223,6 +373,9 @@ impl Display for BattleInfo {
         if self.critical {
             string.push_str(" 💥 Critical hit! 💥");
         }
+        if self.super_effective {
+            string.push_str(" 🎯 Super effective! 🎯");
+        }
         if self.gold_gained > 0 {
             string.push_str("\n\t");
             string.push_str("💰\t");
@@ -347,6 +500,11 @@ trait EnemyEvents {
     fn alignment(&self) -> crate::unit::Alignment;
 
     fn vulnerability(&self) -> Option<damage::DamageType>;
+
+    /// Weights and conditions of the actions, every action always usable unless declared.
+    fn action_rules(&self) -> Vec<crate::enemy::MobActionRule> {
+        self.actions().into_iter().map(Into::into).collect()
+    }
 }
 
 #[cfg(test)]
@@ -355,6 +513,7 @@ mod test {
         character::Character,
         damage::Defense,
         enemy::{Enemy, Mob},
+        rng::CarrionRng,
     };
 
     #[test]
@@ -362,7 +521,7 @@ mod test {
         let mut charater: Character = Default::default();
         charater.level = 30;
         let enemy: Mob = Mob::Eldragor;
-        let enemy: Enemy = enemy.generate(charater.level);
+        let enemy: Enemy = enemy.generate(charater.level, &mut CarrionRng::new(6));
         let defense: Defense = (&enemy).into();
 
         let mitigation = defense.defense(crate::damage::ResistCategories::Physical);
@@ -385,7 +544,7 @@ mod test {
         let mut charater: Character = Default::default();
         charater.level = 350;
         let enemy: Mob = Mob::Eldragor;
-        let enemy: Enemy = enemy.generate(charater.level);
+        let enemy: Enemy = enemy.generate(charater.level, &mut CarrionRng::new(7));
         let defense: Defense = (&enemy).into();
 
         let mitigation = defense.defense(crate::damage::ResistCategories::Physical);
@@ -408,7 +567,7 @@ mod test {
         let mut charater: Character = Default::default();
         charater.level = 30;
         let enemy: Mob = Mob::Orc;
-        let enemy: Enemy = enemy.generate(charater.level);
+        let enemy: Enemy = enemy.generate(charater.level, &mut CarrionRng::new(8));
         let defense: Defense = (&enemy).into();
         let mitigation = defense.defense(crate::damage::ResistCategories::Physical);
         assert!(
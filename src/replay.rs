@@ -0,0 +1,486 @@
+use std::fmt::Display;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::buff::Buff;
+use crate::character::Character;
+use crate::combat;
+use crate::damage::{Damage, DamageType, UniqueDamageEffect};
+use crate::encounter;
+use crate::enemy::Enemy;
+use crate::rng::CarrionRng;
+use crate::simulation::MAX_ROUNDS;
+use crate::skill::{Skill, SkillSet};
+use crate::status::StatusTick;
+use crate::{BattleInfo, CarrionError, CarrionResult};
+
+/// Bump whenever the layout of the log or the order of rolls in combat changes.
+pub const BATTLE_LOG_VERSION: u32 = 13;
+pub const REPLAY_DIR: &str = "replays";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Combatant {
+    Player,
+    Enemy,
+}
+
+impl Display for Combatant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Combatant::Player => write!(f, "Player"),
+            Combatant::Enemy => write!(f, "Enemy"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum BattleEvent {
+    Hit {
+        attacker: Combatant,
+        action: String,
+        dtype: DamageType,
+        rolled: i32,
+        damage: i32,
+        crits: u32,
+        #[serde(default)]
+        super_effective: bool,
+    },
+    Dodge {
+        attacker: Combatant,
+        action: String,
+    },
+    Heal {
+        target: Combatant,
+        action: String,
+        amount: i32,
+    },
+    /// Damage dealt back at whoever hit the `attacker`.
+    Reflect {
+        attacker: Combatant,
+        action: String,
+        amount: i32,
+    },
+    Effect {
+        attacker: Combatant,
+        effect: UniqueDamageEffect,
+    },
+    Buff {
+        action: String,
+        buff: Buff,
+    },
+    Status(StatusTick),
+    Killed(Combatant),
+}
+
+impl BattleEvent {
+    pub(crate) fn hit(
+        attacker: Combatant,
+        action: String,
+        damage: &Damage,
+        rolled: i32,
+        damage_taken: i32,
+        crits: u32,
+        super_effective: bool,
+    ) -> Self {
+        BattleEvent::Hit {
+            attacker,
+            action,
+            dtype: damage.dtype(),
+            rolled,
+            damage: damage_taken,
+            crits,
+            super_effective,
+        }
+    }
+
+    pub(crate) fn dodge(attacker: Combatant, action: String) -> Self {
+        BattleEvent::Dodge { attacker, action }
+    }
+
+    pub(crate) fn heal(target: Combatant, action: String, amount: i32) -> Self {
+        BattleEvent::Heal {
+            target,
+            action,
+            amount,
+        }
+    }
+
+    pub(crate) fn effects(damage: &Damage, events: &mut Vec<BattleEvent>) {
+        events.extend(
+            damage
+                .unique_effect
+                .iter()
+                .map(|effect| BattleEvent::Effect {
+                    attacker: Combatant::Player,
+                    effect: *effect,
+                }),
+        );
+    }
+}
+
+impl Display for BattleEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BattleEvent::Hit {
+                attacker,
+                action,
+                dtype,
+                rolled,
+                damage,
+                crits,
+                super_effective,
+            } => {
+                write!(
+                    f,
+                    "{attacker} used {action} for {damage} {dtype:?} damage ({rolled} before mitigation)"
+                )?;
+                if *crits > 0 {
+                    write!(f, " with {crits} critical hits")?;
+                }
+                if *super_effective {
+                    write!(f, ", super effective")?;
+                }
+                Ok(())
+            }
+            BattleEvent::Dodge { attacker, action } => {
+                write!(f, "{attacker}'s {action} was dodged")
+            }
+            BattleEvent::Heal {
+                target,
+                action,
+                amount,
+            } => write!(f, "{target} healed {amount} with {action}"),
+            BattleEvent::Reflect {
+                attacker,
+                action,
+                amount,
+            } => write!(f, "{attacker} reflected {amount} damage with {action}"),
+            BattleEvent::Effect { attacker, effect } => {
+                write!(f, "{attacker} applied {effect:?}")
+            }
+            BattleEvent::Buff { action, buff } => write!(f, "Player cast {action}: {buff}"),
+            BattleEvent::Status(tick) => write!(f, "{tick}"),
+            BattleEvent::Killed(combatant) => write!(f, "{combatant} was killed"),
+        }
+    }
+}
+
+/// Which combat loop produced the log, the discord battle or the CLI `fight` command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BattleKind {
+    Synchro,
+    Cli,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BattleLogHeader {
+    pub version: u32,
+    pub kind: BattleKind,
+    pub seed: u64,
+    pub character: Character,
+    pub enemies: Vec<Enemy>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BattleRound {
+    pub skill_change: Option<Skill>,
+    pub events: Vec<BattleEvent>,
+}
+
+impl BattleRound {
+    pub fn damage_by(&self, attacker: Combatant) -> i32 {
+        self.events
+            .iter()
+            .map(|event| match event {
+                BattleEvent::Hit {
+                    attacker: hit_by,
+                    damage,
+                    ..
+                } if *hit_by == attacker => *damage,
+                BattleEvent::Reflect {
+                    attacker: reflected_by,
+                    amount,
+                    ..
+                } if *reflected_by == attacker => *amount,
+                _ => 0,
+            })
+            .sum()
+    }
+}
+
+/// Everything needed to play a battle back: the seed, the character and every enemy as they
+/// were before the first blow and every event of every round.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BattleLog {
+    pub header: BattleLogHeader,
+    pub rounds: Vec<BattleRound>,
+}
+
+impl BattleLog {
+    pub fn new(kind: BattleKind, seed: u64, character: &Character, enemies: &[Enemy]) -> Self {
+        Self {
+            header: BattleLogHeader {
+                version: BATTLE_LOG_VERSION,
+                kind,
+                seed,
+                character: character.clone(),
+                enemies: enemies.to_vec(),
+            },
+            rounds: vec![],
+        }
+    }
+
+    /// Runs a discord battle to the end on copies of every combatant and records every round.
+    /// A battle that is still undecided after `MAX_ROUNDS` ends without a winner.
+    pub fn synchro(character: &Character, enemies: &[Enemy], seed: u64) -> (BattleInfo, Self) {
+        let mut recorder = BattleRecorder::new(BattleKind::Synchro, seed, character, enemies);
+        let mut character = character.clone();
+        let mut enemies = enemies.to_vec();
+        while !recorder.finished() && recorder.battle_info.rounds < MAX_ROUNDS {
+            recorder.round(&mut character, &mut enemies, None);
+        }
+        (recorder.battle_info, recorder.log)
+    }
+
+    /// Plays the battle again from the recorded seed and starting state.
+    pub fn replay(&self) -> Self {
+        let header = &self.header;
+        match header.kind {
+            BattleKind::Synchro => Self::synchro(&header.character, &header.enemies, header.seed).1,
+            BattleKind::Cli => {
+                let mut recorder = BattleRecorder::new(
+                    BattleKind::Cli,
+                    header.seed,
+                    &header.character,
+                    &header.enemies,
+                );
+                let mut character = header.character.clone();
+                let mut enemies = header.enemies.clone();
+                for recorded in &self.rounds {
+                    if recorder.finished() {
+                        break;
+                    }
+                    recorder.round(&mut character, &mut enemies, recorded.skill_change);
+                }
+                recorder.log
+            }
+        }
+    }
+
+    /// Replays the log and fails on the first round that plays out differently.
+    pub fn verify(&self) -> CarrionResult<Self> {
+        let replayed = self.replay();
+        for (index, (recorded, played)) in self.rounds.iter().zip(&replayed.rounds).enumerate() {
+            if recorded != played {
+                return Err(CarrionError::ReplayError(format!(
+                    "Round {} diverged, recorded {:?} replayed {:?}",
+                    index + 1,
+                    recorded.events,
+                    played.events
+                )));
+            }
+        }
+        if self.rounds.len() != replayed.rounds.len() {
+            return Err(CarrionError::ReplayError(format!(
+                "Recorded {} rounds but the replay lasted {}",
+                self.rounds.len(),
+                replayed.rounds.len()
+            )));
+        }
+        Ok(replayed)
+    }
+
+    pub fn write(&self, path: &Path) -> CarrionResult<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "{}", serde_json::to_string(&self.header)?)?;
+        for round in &self.rounds {
+            writeln!(writer, "{}", serde_json::to_string(round)?)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn read(path: &Path) -> CarrionResult<Self> {
+        let mut lines = BufReader::new(File::open(path)?).lines();
+        let header: BattleLogHeader = match lines.next() {
+            Some(line) => serde_json::from_str(&line?)?,
+            None => return Err(CarrionError::ReplayError("Empty battle log".to_string())),
+        };
+        if header.version != BATTLE_LOG_VERSION {
+            return Err(CarrionError::ReplayError(format!(
+                "Battle log version {} is not supported, expected {}",
+                header.version, BATTLE_LOG_VERSION
+            )));
+        }
+        let mut rounds = vec![];
+        for line in lines {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            rounds.push(serde_json::from_str(&line)?);
+        }
+        Ok(Self { header, rounds })
+    }
+
+    /// Writes the log into the replay directory and returns where it ended up.
+    pub fn save(&self) -> CarrionResult<PathBuf> {
+        fs::create_dir_all(REPLAY_DIR)?;
+        let path = Path::new(REPLAY_DIR).join(format!(
+            "{}-{}.jsonl",
+            self.header.character.user_id, self.header.seed
+        ));
+        self.write(&path)?;
+        Ok(path)
+    }
+
+    pub fn player_killed(&self) -> bool {
+        self.events()
+            .any(|event| event == &BattleEvent::Killed(Combatant::Player))
+    }
+
+    pub fn events(&self) -> impl Iterator<Item = &BattleEvent> {
+        self.rounds.iter().flat_map(|round| round.events.iter())
+    }
+}
+
+/// A battle in progress. It rolls from its own rng so the log can be replayed on its own.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BattleRecorder {
+    pub(crate) log: BattleLog,
+    pub(crate) rng: CarrionRng,
+    pub(crate) battle_info: BattleInfo,
+}
+
+impl BattleRecorder {
+    pub fn new(kind: BattleKind, seed: u64, character: &Character, enemies: &[Enemy]) -> Self {
+        Self {
+            log: BattleLog::new(kind, seed, character, enemies),
+            rng: CarrionRng::new(seed),
+            battle_info: BattleInfo::begin(character, enemies),
+        }
+    }
+
+    /// Plays one round through the combat engine and appends it to the log.
+    pub fn round(
+        &mut self,
+        character: &mut Character,
+        enemies: &mut [Enemy],
+        skill_change: Option<Skill>,
+    ) -> &BattleRound {
+        if let Some(skill) = skill_change {
+            character.use_skill(SkillSet::new(skill));
+        }
+        let events = combat::round(character, enemies, &mut self.battle_info, &mut self.rng);
+        self.log.rounds.push(BattleRound {
+            skill_change,
+            events,
+        });
+        self.log.rounds.last().expect("A round was just recorded")
+    }
+
+    pub fn finished(&self) -> bool {
+        combat::finished(&self.battle_info)
+    }
+}
+
+impl Display for BattleLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut string = String::new();
+        string.push_str(&format!(
+            "{} vs {} (seed {})",
+            self.header.character.name,
+            encounter::describe(&self.header.enemies),
+            self.header.seed
+        ));
+        for (index, round) in self.rounds.iter().enumerate() {
+            string.push_str(&format!("\nRound {}", index + 1));
+            if let Some(skill) = round.skill_change {
+                string.push_str(&format!("\n\tSwitched to {skill}"));
+            }
+            for event in &round.events {
+                string.push_str(&format!("\n\t{event}"));
+            }
+        }
+        write!(f, "{}", string)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tempdir::TempDir;
+
+    use super::{BattleEvent, BattleKind, BattleLog, BattleRecorder, Combatant};
+    use crate::{
+        character::Character,
+        class::Classes,
+        enemy::{Enemy, Mob},
+        rng::CarrionRng,
+        skill::Skill,
+    };
+
+    fn fighters() -> (Character, Vec<Enemy>) {
+        let character = Character::new("Replay".into(), 1, Classes::Warrior);
+        let mut rng = CarrionRng::new(7);
+        let enemies = vec![
+            Mob::Orc.generate(character.level, &mut rng),
+            Mob::Goblin.generate(character.level, &mut rng),
+        ];
+        (character, enemies)
+    }
+
+    #[test]
+    fn synchro_stalemates_stop_at_the_round_cap() {
+        let (mut character, _) = fighters();
+        character.max_hp = 100_000_000;
+        character.hp = 100_000_000;
+        // Drow glares don't leave a status that scales with the character's health
+        let mut enemies = vec![Mob::Drow.generate(character.level, &mut CarrionRng::new(7))];
+        enemies[0].health = 100_000_000;
+        let (battle_info, log) = BattleLog::synchro(&character, &enemies, 42);
+        assert!(!battle_info.enemy_killed && !battle_info.player_killed);
+        assert_eq!(log.rounds.len(), crate::simulation::MAX_ROUNDS as usize);
+    }
+
+    #[test]
+    fn synchro_replay_round_trip() {
+        let (character, enemies) = fighters();
+        let (_, log) = BattleLog::synchro(&character, &enemies, 42);
+        assert_eq!(log.header.kind, BattleKind::Synchro);
+        assert!(!log.rounds.is_empty());
+
+        let dir = TempDir::new("replay").unwrap();
+        let path = dir.path().join("battle.jsonl");
+        log.write(&path).unwrap();
+        let read = BattleLog::read(&path).unwrap();
+        assert_eq!(read, log);
+        assert_eq!(read.verify().unwrap(), log);
+    }
+
+    #[test]
+    fn cli_replay_follows_skill_changes() {
+        let (mut character, mut enemies) = fighters();
+        let mut recorder = BattleRecorder::new(BattleKind::Cli, 42, &character, &enemies);
+        let skills = [None, Some(Skill::Slash), None, Some(Skill::SteelRain)];
+        for skill_change in skills.iter().cycle().take(50) {
+            if recorder.finished() {
+                break;
+            }
+            recorder.round(&mut character, &mut enemies, *skill_change);
+        }
+        assert!(recorder.log.verify().is_ok());
+    }
+
+    #[test]
+    fn tampered_log_is_rejected() {
+        let (character, enemies) = fighters();
+        let (_, mut log) = BattleLog::synchro(&character, &enemies, 42);
+        log.rounds[0]
+            .events
+            .push(BattleEvent::Killed(Combatant::Enemy));
+        assert!(log.verify().is_err());
+    }
+}
@@ -0,0 +1,48 @@
+use rand::{Error, RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+/// Seedable RNG used for every roll in the combat pipeline.
+/// A battle started from the same seed and `Character` snapshot always plays out the same way.
+/// ChaCha8 keeps its stream fixed across `rand` versions and platforms, unlike `StdRng`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CarrionRng {
+    seed: u64,
+    rng: ChaCha8Rng,
+}
+
+impl CarrionRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl Default for CarrionRng {
+    fn default() -> Self {
+        Self::new(rand::random())
+    }
+}
+
+impl RngCore for CarrionRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
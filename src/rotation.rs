@@ -0,0 +1,229 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use crate::encounter::vulnerable;
+use crate::enemy::Enemy;
+use crate::skill::{Skill, SkillSet};
+use crate::{CarrionError, CarrionResult};
+
+pub const MAX_ROTATION_SKILLS: usize = 4;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RotationPolicy {
+    /// Cycle through the skills one action point at a time.
+    #[default]
+    RoundRobin,
+    /// Always use the first skill in the list that can be used.
+    Priority,
+}
+
+/// When a skill in a rotation can be used.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SkillRule {
+    #[default]
+    Always,
+    /// While the character is below this share of their max HP, in percent.
+    HpBelow(u32),
+    /// While any living enemy is vulnerable to the skill's element.
+    Vulnerable,
+}
+
+impl SkillRule {
+    fn holds(&self, skill: Skill, hp: i32, max_hp: u32, enemies: &[Enemy]) -> bool {
+        match self {
+            SkillRule::Always => true,
+            SkillRule::HpBelow(percent) => {
+                (hp.max(0) as u64) * 100 < max_hp as u64 * *percent as u64
+            }
+            SkillRule::Vulnerable => enemies
+                .iter()
+                .any(|enemy| enemy.alive() && vulnerable(skill, enemy)),
+        }
+    }
+}
+
+impl TryFrom<&str> for SkillRule {
+    type Error = CarrionError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let rule = value.to_lowercase();
+        if rule == "vulnerable" {
+            return Ok(SkillRule::Vulnerable);
+        }
+        rule.strip_prefix("hp<")
+            .map(|percent| percent.trim_end_matches('%'))
+            .and_then(|percent| percent.parse().ok())
+            .filter(|percent| (1..=100).contains(percent))
+            .map(SkillRule::HpBelow)
+            .ok_or_else(|| {
+                CarrionError::RotationError(format!(
+                    "Unknown rule {}, use hp<30 or vulnerable",
+                    value
+                ))
+            })
+    }
+}
+
+impl Display for SkillRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SkillRule::Always => write!(f, "always"),
+            SkillRule::HpBelow(percent) => write!(f, "below {}% HP", percent),
+            SkillRule::Vulnerable => write!(f, "enemy vulnerable"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RotationSkill {
+    pub skill: SkillSet,
+    pub rule: SkillRule,
+}
+
+/// Up to four skills a character cycles through instead of a single `current_skill`. Every
+/// skill keeps its own `SkillSet` so each one levels from the action points it was used for.
+/// Skills with a rule jump the queue while it holds in a round robin, a priority list just
+/// takes the first usable skill. When nothing can be used the first skill is.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Rotation {
+    pub policy: RotationPolicy,
+    skills: Vec<RotationSkill>,
+    next: usize,
+}
+
+impl Rotation {
+    pub fn new(policy: RotationPolicy, skills: Vec<RotationSkill>) -> Self {
+        Self {
+            policy,
+            skills,
+            next: 0,
+        }
+    }
+
+    /// Parses an optional policy followed by skills with optional rules, like
+    /// `priority Mend:hp<30 Fireball:vulnerable Slash`.
+    pub fn parse(args: &[&str]) -> CarrionResult<Self> {
+        let (policy, args) = match args.first().map(|arg| arg.to_lowercase()).as_deref() {
+            Some("priority") => (RotationPolicy::Priority, &args[1..]),
+            Some("round_robin" | "roundrobin" | "cycle") => {
+                (RotationPolicy::RoundRobin, &args[1..])
+            }
+            _ => (RotationPolicy::RoundRobin, args),
+        };
+        let skills = args
+            .iter()
+            .map(|arg| {
+                let (skill, rule) = match arg.split_once(':') {
+                    Some((skill, rule)) => (skill, SkillRule::try_from(rule)?),
+                    None => (*arg, SkillRule::Always),
+                };
+                let skill = Skill::try_from(skill.to_string())
+                    .map_err(|_| CarrionError::RotationError(format!("Unknown skill {}", skill)))?;
+                Ok(RotationSkill {
+                    skill: SkillSet::new(skill),
+                    rule,
+                })
+            })
+            .collect::<CarrionResult<Vec<_>>>()?;
+        Ok(Self::new(policy, skills))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.skills.is_empty()
+    }
+
+    pub fn skills(&self) -> impl Iterator<Item = Skill> + '_ {
+        self.skills.iter().map(|slot| slot.skill.skill())
+    }
+
+    pub fn skill_sets(&self) -> impl Iterator<Item = &SkillSet> {
+        self.skills.iter().map(|slot| &slot.skill)
+    }
+
+    pub fn skill_sets_mut(&mut self) -> impl Iterator<Item = &mut SkillSet> {
+        self.skills.iter_mut().map(|slot| &mut slot.skill)
+    }
+
+    /// Picks the skill for the next action point.
+    pub fn next(&mut self, hp: i32, max_hp: u32, enemies: &[Enemy]) -> Option<SkillSet> {
+        let holds = |slot: &RotationSkill| slot.rule.holds(slot.skill.skill(), hp, max_hp, enemies);
+        let index = match self.policy {
+            RotationPolicy::Priority => self.skills.iter().position(holds),
+            RotationPolicy::RoundRobin => self
+                .skills
+                .iter()
+                .position(|slot| slot.rule != SkillRule::Always && holds(slot))
+                .or_else(|| {
+                    let len = self.skills.len();
+                    let index = (0..len)
+                        .map(|offset| (self.next + offset) % len)
+                        .find(|index| self.skills[*index].rule == SkillRule::Always)?;
+                    self.next = index + 1;
+                    Some(index)
+                }),
+        };
+        self.skills
+            .get(index.unwrap_or(0))
+            .map(|slot| slot.skill.clone())
+    }
+}
+
+impl Display for Rotation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let skills: Vec<String> = self
+            .skills
+            .iter()
+            .map(|slot| match slot.rule {
+                SkillRule::Always => format!("{} (level {})", slot.skill.skill(), slot.skill.level),
+                rule => format!(
+                    "{} (level {}, {})",
+                    slot.skill.skill(),
+                    slot.skill.level,
+                    rule
+                ),
+            })
+            .collect();
+        write!(f, "{:?}: {}", self.policy, skills.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Rotation, RotationPolicy, SkillRule};
+    use crate::{enemy::Mob, rng::CarrionRng, skill::Skill};
+
+    #[test]
+    fn rules_jump_the_queue() {
+        let mut rng = CarrionRng::new(16);
+        let enemies = [Mob::Orc.generate(1, &mut rng)];
+        assert!(Rotation::parse(&["Slash:hp<0"]).is_err());
+        assert!(Rotation::parse(&["Nothing"]).is_err());
+
+        let mut rotation = Rotation::parse(&["Mend:hp<30", "Slash", "Backstab"]).unwrap();
+        assert_eq!(rotation.policy, RotationPolicy::RoundRobin);
+        let picks: Vec<Skill> = (0..3)
+            .map(|_| rotation.next(100, 100, &enemies).unwrap().skill())
+            .collect();
+        assert_eq!(picks, vec![Skill::Slash, Skill::Backstab, Skill::Slash]);
+        assert_eq!(
+            rotation.next(20, 100, &enemies).unwrap().skill(),
+            Skill::Mend
+        );
+
+        let mut rotation =
+            Rotation::parse(&["priority", "Mend:hp<30", "Slash", "Backstab"]).unwrap();
+        assert_eq!(
+            rotation.next(100, 100, &enemies).unwrap().skill(),
+            Skill::Slash
+        );
+        assert_eq!(
+            rotation.next(100, 100, &enemies).unwrap().skill(),
+            Skill::Slash
+        );
+        assert_eq!(
+            SkillRule::try_from("HP<30%").unwrap(),
+            SkillRule::HpBelow(30)
+        );
+    }
+}
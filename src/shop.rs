@@ -0,0 +1,159 @@
+use std::fmt::Display;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::item::{IndividualItem, Items, Rarity};
+use crate::items::generate;
+use crate::rng::CarrionRng;
+use crate::{CarrionError, CarrionResult};
+
+/// Everyone sees the same stock until the shop restocks.
+pub const RESTOCK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+pub const STOCK_SIZE: usize = 6;
+pub const GAMBLE_COST: u64 = 1_000;
+
+/// Generated items can not go past Epic. The discriminants double as how often each shows up.
+const STOCKED: [Rarity; 5] = [
+    Rarity::Common,
+    Rarity::Uncommon,
+    Rarity::Rare,
+    Rarity::VeryRare,
+    Rarity::Epic,
+];
+
+pub fn price(rarity: Rarity) -> u64 {
+    rarity.item_points() / 4
+}
+
+fn roll_rarity(rng: &mut impl Rng) -> Rarity {
+    *STOCKED
+        .choose_weighted(rng, |rarity| *rarity as u64)
+        .expect("Shop has no rarities to stock")
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shop {
+    restock: u64,
+    stock: Vec<IndividualItem>,
+}
+
+impl Shop {
+    /// The stock only depends on `restock`, so it does not have to be stored anywhere.
+    pub fn new(restock: u64) -> Self {
+        let mut rng = CarrionRng::new(restock);
+        let mut stock: Vec<IndividualItem> = (0..STOCK_SIZE)
+            .map(|_| {
+                let rarity = roll_rarity(&mut rng);
+                generate(rarity, &mut rng)
+            })
+            .collect();
+        stock.sort_by_key(|item| price(item.rarity));
+        Self { restock, stock }
+    }
+
+    pub fn current() -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        Self::new(now.as_secs() / RESTOCK_INTERVAL.as_secs())
+    }
+
+    pub fn stock(&self) -> &[IndividualItem] {
+        &self.stock
+    }
+
+    /// Items are numbered from 1 the way they are listed.
+    pub fn get(&self, number: usize) -> Option<&IndividualItem> {
+        self.stock.get(number.checked_sub(1)?)
+    }
+
+    pub(crate) fn display_for_cli(&self) -> Vec<String> {
+        let s = self.to_string();
+        s.lines()
+            .filter(|line| !line.is_empty() && *line != "```")
+            .map(|line| line.to_string())
+            .collect()
+    }
+}
+
+impl Display for Shop {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut string = String::new();
+        string.push_str("\n```\n");
+        for (number, item) in self.stock.iter().enumerate() {
+            string.push_str(&format!(
+                "{}. {} ({:?}) - {} gold\n",
+                number + 1,
+                item.name,
+                item.rarity,
+                price(item.rarity)
+            ));
+        }
+        string.push_str(&format!("Gamble - {} gold\n", GAMBLE_COST));
+        string.push_str("```\n");
+        write!(f, "{}", string)
+    }
+}
+
+fn pay(items: &mut Items, cost: u64) -> CarrionResult<()> {
+    if items.gold < cost {
+        return Err(CarrionError::ShopError(format!(
+            "{} gold is needed, you have {}",
+            cost, items.gold
+        )));
+    }
+    items.gold -= cost;
+    Ok(())
+}
+
+/// Moves the item into the stash and takes its price from the gold.
+pub fn buy(items: &mut Items, item: IndividualItem) -> CarrionResult<IndividualItem> {
+    if items.iter().any(|owned| *owned == item) {
+        return Err(CarrionError::ShopError(format!(
+            "You already own {}",
+            item.name
+        )));
+    }
+    pay(items, price(item.rarity))?;
+    items.stash(item.clone());
+    Ok(item)
+}
+
+/// Pays `GAMBLE_COST` for an item of a random rarity in a random slot.
+pub fn gamble(items: &mut Items, rng: &mut impl Rng) -> CarrionResult<IndividualItem> {
+    pay(items, GAMBLE_COST)?;
+    let item = generate(roll_rarity(rng), rng);
+    items.stash(item.clone());
+    Ok(item)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{buy, gamble, price, Shop, GAMBLE_COST, STOCK_SIZE};
+    use crate::{item::Items, rng::CarrionRng};
+
+    #[test]
+    fn purchases_cost_gold() {
+        let shop = Shop::new(7);
+        assert_eq!(shop, Shop::new(7));
+        assert_eq!(shop.stock().len(), STOCK_SIZE);
+        assert!(shop.get(0).is_none());
+
+        let item = shop.get(1).unwrap().clone();
+        let mut items = Items::default();
+        assert!(buy(&mut items, item.clone()).is_err());
+
+        items.gold = price(item.rarity) + GAMBLE_COST;
+        assert!(buy(&mut items, item.clone()).is_ok());
+        assert!(buy(&mut items, item).is_err());
+        assert_eq!(items.gold, GAMBLE_COST);
+
+        let mut rng = CarrionRng::new(7);
+        assert!(gamble(&mut items, &mut rng).is_ok());
+        assert!(gamble(&mut items, &mut rng).is_err());
+        assert_eq!(items.gold, 0);
+        assert_eq!(items.iter().count(), 2);
+    }
+}
@@ -0,0 +1,212 @@
+use rand::Rng;
+use serde::Serialize;
+use strum::IntoEnumIterator;
+
+use crate::character::Character;
+use crate::class::Classes;
+use crate::combat;
+use crate::enemy::Mob;
+use crate::rng::CarrionRng;
+use crate::skill::{Skill, SkillSet};
+use crate::BattleInfo;
+
+/// Fights that are still going after this many rounds count as a loss.
+pub const MAX_ROUNDS: u32 = 500;
+
+#[derive(Debug, Clone)]
+pub struct SimulationConfig {
+    pub fights: u32,
+    pub seed: u64,
+    pub levels: Vec<u32>,
+    pub classes: Vec<Classes>,
+    pub skills: Vec<Skill>,
+    pub mobs: Vec<Mob>,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self {
+            fights: 100,
+            seed: 0,
+            levels: vec![1, 5, 10, 25, 50, 100],
+            classes: Classes::iter().collect(),
+            skills: Skill::iter().collect(),
+            mobs: Mob::iter().collect(),
+        }
+    }
+}
+
+/// Averages over every fight of a single class, skill, mob and level combination.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MatchupReport {
+    pub class: Classes,
+    pub skill: Skill,
+    pub mob: Mob,
+    pub level: u32,
+    /// Whether the class meets the attribute requirement to pick the skill in game.
+    pub usable: bool,
+    pub fights: u32,
+    pub win_rate: f64,
+    pub rounds_to_kill: f64,
+    pub damage_per_action_point: f64,
+    pub gold_per_fight: f64,
+    pub experience_per_fight: f64,
+}
+
+impl MatchupReport {
+    pub const CSV_HEADER: &'static str = "class,skill,mob,level,usable,fights,win_rate,rounds_to_kill,damage_per_action_point,gold_per_fight,experience_per_fight";
+
+    pub fn csv_row(&self) -> String {
+        format!(
+            "{:?},{:?},{:?},{},{},{},{:.3},{:.2},{:.2},{:.2},{:.2}",
+            self.class,
+            self.skill,
+            self.mob,
+            self.level,
+            self.usable,
+            self.fights,
+            self.win_rate,
+            self.rounds_to_kill,
+            self.damage_per_action_point,
+            self.gold_per_fight,
+            self.experience_per_fight
+        )
+    }
+}
+
+pub fn character_at_level(class: Classes, skill: Skill, level: u32) -> Character {
+    let mut character = Character::new(format!("{class:?}"), 0, class);
+    while character.level < level {
+        character.experience = character.experience_to_next_level();
+        character.try_level_up();
+    }
+    character.current_skill = SkillSet::new(skill);
+    character
+}
+
+/// Runs the combat engine capped at `MAX_ROUNDS`. Returns the battle and the rounds it took.
+pub fn fight(character: &Character, mob: Mob, rng: &mut impl Rng) -> (BattleInfo, u32) {
+    let mut character = character.clone();
+    let mut enemies = [mob.generate(character.level, rng)];
+    let mut battle_info = BattleInfo::begin(&character, &enemies);
+    let mut rounds = 0;
+    while !combat::finished(&battle_info) && rounds < MAX_ROUNDS {
+        rounds += 1;
+        combat::round(&mut character, &mut enemies, &mut battle_info, rng);
+    }
+    (battle_info, rounds)
+}
+
+pub fn matchup(
+    class: Classes,
+    skill: Skill,
+    mob: Mob,
+    level: u32,
+    fights: u32,
+    rng: &mut impl Rng,
+) -> MatchupReport {
+    let character = character_at_level(class, skill, level);
+    let mut wins = 0;
+    let mut winning_rounds = 0;
+    let mut damage = 0i64;
+    let mut action_points = 0i64;
+    let mut gold = 0;
+    let mut experience = 0;
+
+    for _ in 0..fights {
+        let (battle_info, rounds) = fight(&character, mob, rng);
+        if battle_info.enemy_killed {
+            wins += 1;
+            winning_rounds += rounds;
+        }
+        damage += battle_info.player_damage as i64;
+        action_points += battle_info.number_of_player_attacks as i64;
+        gold += battle_info.gold_gained;
+        experience += battle_info.experience_gained;
+    }
+
+    let per_fight = |total: u64| total as f64 / fights.max(1) as f64;
+    MatchupReport {
+        class,
+        skill,
+        mob,
+        level,
+        usable: character.skill_list().contains(&skill),
+        fights,
+        win_rate: per_fight(wins),
+        rounds_to_kill: winning_rounds as f64 / wins.max(1) as f64,
+        damage_per_action_point: damage as f64 / action_points.max(1) as f64,
+        gold_per_fight: per_fight(gold),
+        experience_per_fight: per_fight(experience),
+    }
+}
+
+/// Runs every combination in the config across all cores. Each matchup gets its own seed so
+/// a row can be reproduced without running the whole matrix.
+pub fn simulate(config: &SimulationConfig) -> Vec<MatchupReport> {
+    let mut seeds = CarrionRng::new(config.seed);
+    let mut jobs = vec![];
+    for class in &config.classes {
+        for skill in &config.skills {
+            for mob in &config.mobs {
+                for level in &config.levels {
+                    jobs.push((*class, *skill, *mob, *level, seeds.gen::<u64>()));
+                }
+            }
+        }
+    }
+
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk_size = (jobs.len() / threads).max(1);
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = jobs
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|(class, skill, mob, level, seed)| {
+                            let mut rng = CarrionRng::new(*seed);
+                            matchup(*class, *skill, *mob, *level, config.fights, &mut rng)
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("Simulation thread panicked"))
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{character_at_level, simulate, SimulationConfig};
+    use crate::{class::Classes, enemy::Mob, skill::Skill};
+
+    #[test]
+    fn characters_reach_the_requested_level() {
+        let character = character_at_level(Classes::Warrior, Skill::Slash, 25);
+        assert_eq!(character.level, 25);
+        assert!(character.max_hp > 120);
+    }
+
+    #[test]
+    fn simulation_is_reproducible() {
+        let config = SimulationConfig {
+            fights: 20,
+            levels: vec![1, 10],
+            classes: vec![Classes::Paladin],
+            skills: vec![Skill::Slash],
+            mobs: vec![Mob::Orc],
+            ..Default::default()
+        };
+        let reports = simulate(&config);
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports, simulate(&config));
+        for report in reports {
+            assert!((0.0..=1.0).contains(&report.win_rate));
+        }
+    }
+}
@@ -1,17 +1,22 @@
+use crate::buff::BuffKind;
 use crate::character::Character;
 use crate::damage::{Damage, DamageBuilder, DamageType, UniqueDamageEffect};
 use crate::enemy::Enemy;
 use crate::unit::Attributes;
 use crate::{
     enemy_damage_scaling, level_up_scaling, log_power_scale, AttributeScaling, ElementalScaling,
-    EnemyEvents,
+    EnemyEvents, ValidEnum,
 };
 use eris_macro::{AttributeScaling, ElementalScaling, ErisDisplayEmoji, ErisValidEnum};
-use rand::{thread_rng, Rng};
+use rand::Rng;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fmt::Display;
 use strum::IntoEnumIterator;
 
+/// Skill level both skills of a recipe need to unlock its ascended skill.
+pub const MASTERY_LEVEL: u32 = 50;
+
 #[derive(
     Debug,
     Clone,
@@ -160,6 +165,66 @@ pub enum Skill {
     #[stat("strength")]
     #[emoji("✨")]
     EtherealCrush,
+    #[element("NonElemental")]
+    #[stat("constitution")]
+    #[emoji("🪨")]
+    StoneSkin,
+    #[element("NonElemental")]
+    #[stat("charisma")]
+    #[emoji("🔰")]
+    DivineAegis,
+    #[element("NonElemental")]
+    #[stat("wisdom")]
+    #[emoji("🫧")]
+    ManaShield,
+    #[element("NonElemental")]
+    #[stat("dexterity")]
+    #[emoji("💨")]
+    Blur,
+    #[element("NonElemental")]
+    #[stat("wisdom")]
+    #[emoji("⏩")]
+    Haste,
+    #[element("NonElemental")]
+    #[stat("charisma")]
+    #[emoji("📯")]
+    BattleCry,
+    #[element("NonElemental")]
+    #[stat("wisdom")]
+    #[emoji("🩹")]
+    Mend,
+    #[element("NonElemental")]
+    #[stat("charisma")]
+    #[emoji("🤲")]
+    LayOnHands,
+    // Ascended skills stay hidden until their recipe is mastered, see `Skill::recipe`
+    #[element("Physical")]
+    #[stat("strength")]
+    #[emoji("⚔️")]
+    #[hidden]
+    Worldsplitter,
+    #[element("Elemental")]
+    #[stat("intelligence")]
+    #[emoji("♨️")]
+    #[hidden]
+    Steamburst,
+    #[element("NonElemental")]
+    #[stat("wisdom")]
+    #[emoji("⚖️")]
+    #[hidden]
+    Judgement,
+    #[element("NonElemental")]
+    #[stat("dexterity")]
+    #[emoji("🌘")]
+    #[hidden]
+    Eclipse,
+}
+
+/// What a support skill does instead of dealing damage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Support {
+    Buff(BuffKind),
+    Heal,
 }
 
 impl Default for Skill {
@@ -169,12 +234,12 @@ impl Default for Skill {
 }
 
 impl Skill {
-    fn funky_scaling(&self, damage: &mut Damage, _player: &Character) {
+    fn funky_scaling(&self, damage: &mut Damage, _player: &Character, rng: &mut impl Rng) {
         match self {
             Skill::Slash => {
                 damage.critical_multiplier = 2.0;
                 damage.crit_chance += 0.1;
-                if thread_rng().gen_bool(0.02) {
+                if rng.gen_bool(0.02) {
                     damage.unique_effect.push(UniqueDamageEffect::Berserk)
                 }
             }
@@ -182,14 +247,14 @@ impl Skill {
                 damage.number_of_hits = 5;
             }
             Skill::FireBall => {
-                if thread_rng().gen_bool(0.1) {
+                if rng.gen_bool(0.1) {
                     damage.unique_effect.push(UniqueDamageEffect::Burn)
                 }
                 damage.critical_multiplier = 2.5;
                 damage.crit_chance = 0.15;
             }
             Skill::WaterBall => {
-                if thread_rng().gen_bool(0.1) {
+                if rng.gen_bool(0.1) {
                     damage.unique_effect.push(UniqueDamageEffect::Shock)
                 }
                 damage.damage = 12;
@@ -198,12 +263,12 @@ impl Skill {
                 damage.damage = 20;
             }
             Skill::PoisonFlask => {
-                if thread_rng().gen_bool(0.2) {
+                if rng.gen_bool(0.2) {
                     damage.unique_effect.push(UniqueDamageEffect::Poison)
                 }
             }
             Skill::SteelRain => {
-                if thread_rng().gen_bool(0.1) {
+                if rng.gen_bool(0.1) {
                     damage.unique_effect.push(UniqueDamageEffect::Bleed)
                 }
                 damage.number_of_hits = 5;
@@ -220,28 +285,29 @@ impl Skill {
                 damage.damage += 5;
             }
             Skill::Earthquake => {
-                damage.number_of_hits = thread_rng().gen_range(1..15);
+                damage.number_of_hits = rng.gen_range(1..15);
                 damage.damage -= 15;
             }
             Skill::HolySmite => {
-                if thread_rng().gen_bool(0.01) {
+                if rng.gen_bool(0.01) {
                     damage.multiplier = 1.6;
                 };
                 damage.damage += 5;
+                damage.alignment = Some(crate::unit::Alignment::LawfulGood);
             }
             Skill::DivineBlessing => {
-                if thread_rng().gen_bool(0.07) {
+                if rng.gen_bool(0.07) {
                     damage.unique_effect.push(UniqueDamageEffect::Regenerate)
                 }
             }
             Skill::SuicidalPersuasion => {
-                if thread_rng().gen_bool(0.035) {
+                if rng.gen_bool(0.035) {
                     damage.unique_effect.push(UniqueDamageEffect::Death)
                 };
             }
             Skill::Seduction => {
                 damage.damage += 5;
-                if thread_rng().gen_bool(0.15) {
+                if rng.gen_bool(0.15) {
                     damage.unique_effect.push(UniqueDamageEffect::Curse)
                 }
             }
@@ -249,7 +315,7 @@ impl Skill {
                 damage.damage += 5;
             }
             Skill::Excoriate => {
-                if thread_rng().gen_bool(0.1) {
+                if rng.gen_bool(0.1) {
                     damage.unique_effect.push(UniqueDamageEffect::Burn)
                 }
             }
@@ -275,11 +341,11 @@ impl Skill {
                 damage.damage += 5;
             }
             Skill::IronFusillade => {
-                damage.number_of_hits = thread_rng().gen_range(1..7);
+                damage.number_of_hits = rng.gen_range(1..7);
             }
             Skill::PrismaticHowl => {
                 damage.damage += 5;
-                if thread_rng().gen_bool(0.05) {
+                if rng.gen_bool(0.05) {
                     damage.unique_effect.push(UniqueDamageEffect::Shock);
                     damage.unique_effect.push(UniqueDamageEffect::Curse);
                     damage.unique_effect.push(UniqueDamageEffect::Burn);
@@ -287,13 +353,13 @@ impl Skill {
             }
             Skill::MightyBlow => {
                 damage.damage += 50;
-                if thread_rng().gen_bool(0.1) {
+                if rng.gen_bool(0.1) {
                     damage.unique_effect.push(UniqueDamageEffect::Enrage);
                 }
             }
             Skill::NebulaHammer => {
                 damage.damage += 50;
-                if thread_rng().gen_bool(0.1) {
+                if rng.gen_bool(0.1) {
                     damage.unique_effect.push(UniqueDamageEffect::Enrage);
                     damage.unique_effect.push(UniqueDamageEffect::Berserk);
                 }
@@ -312,8 +378,8 @@ impl Skill {
                 damage.alignment = Some(crate::unit::Alignment::ChaoticEvil);
             }
             Skill::EtherealCrush => {
-                damage.damage += thread_rng().gen_range(0..100);
-                if thread_rng().gen_bool(0.01) {
+                damage.damage += rng.gen_range(0..100);
+                if rng.gen_bool(0.01) {
                     damage.unique_effect.push(UniqueDamageEffect::Death);
                 }
             }
@@ -321,21 +387,115 @@ impl Skill {
                 let unique_effect: Vec<_> = UniqueDamageEffect::iter().collect();
                 damage.unique_effect.extend(unique_effect)
             }
+            Skill::Worldsplitter => {
+                damage.damage += 60;
+                damage.critical_multiplier = 2.5;
+                damage.crit_chance += 0.15;
+                if rng.gen_bool(0.05) {
+                    damage.unique_effect.push(UniqueDamageEffect::Berserk)
+                }
+            }
+            Skill::Steamburst => {
+                damage.number_of_hits = 3;
+                damage.damage += 20;
+                if rng.gen_bool(0.15) {
+                    damage.unique_effect.push(UniqueDamageEffect::Burn);
+                    damage.unique_effect.push(UniqueDamageEffect::Shock);
+                }
+            }
+            Skill::Judgement => {
+                damage.damage += 50;
+                damage.alignment = Some(crate::unit::Alignment::LawfulGood);
+                if rng.gen_bool(0.1) {
+                    damage.unique_effect.push(UniqueDamageEffect::Regenerate)
+                }
+            }
+            Skill::Eclipse => {
+                damage.number_of_hits = 2;
+                damage.damage += 30;
+                damage.crit_chance += 0.2;
+                if rng.gen_bool(0.1) {
+                    damage.unique_effect.push(UniqueDamageEffect::Bleed)
+                }
+            }
+            Skill::StoneSkin
+            | Skill::DivineAegis
+            | Skill::ManaShield
+            | Skill::Blur
+            | Skill::Haste
+            | Skill::BattleCry
+            | Skill::Mend
+            | Skill::LayOnHands => {}
+        }
+    }
+
+    /// Support skills buff or heal the player, they attack with their class skill while the
+    /// buff is up or their HP is high enough that healing would be wasted.
+    pub fn support(&self) -> Option<Support> {
+        match self {
+            Skill::StoneSkin | Skill::DivineAegis => Some(Support::Buff(BuffKind::Armor)),
+            Skill::ManaShield => Some(Support::Buff(BuffKind::Ward)),
+            Skill::Blur => Some(Support::Buff(BuffKind::Dodge)),
+            Skill::Haste => Some(Support::Buff(BuffKind::Haste)),
+            Skill::BattleCry => Some(Support::Buff(BuffKind::Empower)),
+            Skill::Mend | Skill::LayOnHands => Some(Support::Heal),
+            _ => None,
         }
     }
 
-    pub fn base_damage(&self, player: &Character) -> Damage {
+    pub fn is_support(&self) -> bool {
+        self.support().is_some()
+    }
+
+    /// The two skills to master to unlock an ascended skill, `None` for every other skill.
+    pub fn recipe(&self) -> Option<(Skill, Skill)> {
+        match self {
+            Skill::Worldsplitter => Some((Skill::Slash, Skill::PowerStrike)),
+            Skill::Steamburst => Some((Skill::FireBall, Skill::WaterBall)),
+            Skill::Judgement => Some((Skill::HolySmite, Skill::DivineBlessing)),
+            Skill::Eclipse => Some((Skill::Backstab, Skill::ShadowStrike)),
+            _ => None,
+        }
+    }
+
+    pub fn is_ascended(&self) -> bool {
+        self.recipe().is_some()
+    }
+
+    /// `Skill::valid()` with the ascended skills in `unlocked`, the others stay secret.
+    pub fn valid_for(unlocked: &[Skill]) -> String {
+        let mut valid = Skill::valid();
+        for skill in unlocked {
+            valid.push('\n');
+            valid.push_str(&skill.to_string());
+        }
+        valid
+    }
+
+    /// Area skills hit every enemy of an encounter instead of the targeted one.
+    pub fn is_area(&self) -> bool {
+        matches!(
+            self,
+            Skill::Earthquake
+                | Skill::Tornado
+                | Skill::SteelRain
+                | Skill::EarthShatter
+                | Skill::EarthquakeSlam
+        )
+    }
+
+    pub fn base_damage(&self, player: &Character, rng: &mut impl Rng) -> Damage {
         let mut base = DamageBuilder::default()
             .dtype(self.element().unwrap_or_default())
             .damage(0)
             .build()
             .unwrap();
 
-        self.funky_scaling(&mut base, player);
+        self.funky_scaling(&mut base, player, rng);
 
         let player_attributes = player.attributes.clone() + player.equipment.attribute();
         let attribute_bonus = self.attribute(&player_attributes);
-        let elemental_bonus = self.elemental_scaling() * player.level as i32;
+        let elemental_bonus = self.elemental_scaling(rng) * player.level as i32;
         base.damage +=
             (attribute_bonus + elemental_bonus).saturating_div(base.number_of_hits as i32);
         base.damage = base.damage.max(1);
@@ -358,7 +518,7 @@ impl Skill {
         ElementalScaling::scaling(self)
     }
 
-    fn elemental_scaling(&self) -> i32 {
+    fn elemental_scaling(&self, rng: &mut impl Rng) -> i32 {
         if let Some(elemental) = ElementalScaling::scaling(self) {
             let (bottom, top) = match elemental {
                 DamageType::NonElemental => (0, 10),
@@ -369,12 +529,29 @@ impl Skill {
                 DamageType::Universal => (0, 10),
                 DamageType::Elemental => (0, 10),
             };
-            return thread_rng().gen_range(bottom..top);
+            return rng.gen_range(bottom..top);
         }
-        thread_rng().gen_range(0..10)
+        rng.gen_range(0..10)
     }
 }
 
+/// Ascended skills whose recipes are mastered at `levels` and are not `unlocked` yet.
+pub fn discover(levels: &HashMap<Skill, u32>, unlocked: &[Skill]) -> Vec<Skill> {
+    let mastered = |skill: &Skill| {
+        levels
+            .get(skill)
+            .is_some_and(|level| *level >= MASTERY_LEVEL)
+    };
+    Skill::iter()
+        .filter(|skill| !unlocked.contains(skill))
+        .filter(|skill| {
+            skill
+                .recipe()
+                .is_some_and(|(first, second)| mastered(&first) && mastered(&second))
+        })
+        .collect()
+}
+
 #[derive(
     Debug,
     Clone,
@@ -482,7 +659,7 @@ pub enum MobAction {
     #[element("prismatic")]
     FrostBreath,
 
-    #[element("elemntal")]
+    #[element("elemental")]
     #[stat("intelligence")]
     #[emoji("🔥")]
     DragonBreath,
@@ -499,7 +676,7 @@ pub enum MobAction {
 }
 
 impl MobAction {
-    pub fn base_damage(&self, enemy: &Enemy) -> Damage {
+    pub fn base_damage(&self, enemy: &Enemy, rng: &mut impl Rng) -> Damage {
         let element = ElementalScaling::scaling(self).unwrap_or_default();
         let mut base = DamageBuilder::default()
             .dtype(element)
@@ -509,15 +686,29 @@ impl MobAction {
             .build()
             .unwrap();
         base.damage += self.attribute(&enemy.attributes);
-        base.damage += self.elemental_scaling();
+        base.damage += self.elemental_scaling(rng);
 
         if ElementalScaling::scaling(self) == Some(DamageType::Healing) {
             base.multiplier = 0.2;
             base.crit_chance = 0.0;
         }
+        base.unique_effect.extend(self.affliction());
         base
     }
 
+    /// The status effect a hit of the action leaves on the character.
+    pub fn affliction(&self) -> Option<UniqueDamageEffect> {
+        use MobAction::*;
+        match self {
+            Bite | SlimeAbsorb => Some(UniqueDamageEffect::Poison),
+            Claw => Some(UniqueDamageEffect::Bleed),
+            Burn | DragonBreath => Some(UniqueDamageEffect::Burn),
+            MindBreak => Some(UniqueDamageEffect::Shock),
+            NecroticBlast | ShadowNova => Some(UniqueDamageEffect::Curse),
+            _ => None,
+        }
+    }
+
     pub(crate) fn attribute(&self, attributes: &Attributes) -> i32 {
         (if let Some(attribute) = AttributeScaling::scaling(self) {
             let attribute_value = attributes.get(&attribute);
@@ -526,7 +717,7 @@ impl MobAction {
             0
         }) as i32
     }
-    fn elemental_scaling(&self) -> i32 {
+    fn elemental_scaling(&self, rng: &mut impl Rng) -> i32 {
         if let Some(elemental) = ElementalScaling::scaling(self) {
             let (bottom, top) = match elemental {
                 DamageType::NonElemental => (0, 10),
@@ -537,9 +728,9 @@ impl MobAction {
                 DamageType::Universal => (0, 10),
                 DamageType::Elemental => (0, 10),
             };
-            return thread_rng().gen_range(bottom..top);
+            return rng.gen_range(bottom..top);
         }
-        thread_rng().gen_range(0..10)
+        rng.gen_range(0..10)
     }
 }
 
@@ -613,8 +804,8 @@ impl SkillSet {
         self.skill
     }
 
-    pub fn act(&self, player: &Character, enemy: &Enemy) -> Damage {
-        let mut base = self.skill().base_damage(player);
+    pub fn act(&self, player: &Character, enemy: &Enemy, rng: &mut impl Rng) -> Damage {
+        let mut base = self.skill().base_damage(player, rng);
         base.damage += self.action_experience_scaling();
         let mutations = player.mutations();
         base += mutations
@@ -631,6 +822,8 @@ impl SkillSet {
             .get(&DamageType::Universal)
             .unwrap_or(&0);
         base.number_of_hits += player.equipment.action_points() as u32;
+        base.multiplier += player.buffs.damage_multiplier();
+        base.multiplier += mutations.last_stand(player.hp, player.max_hp);
 
         base
     }
@@ -638,6 +831,21 @@ impl SkillSet {
         level_up_scaling(self.level, None)
     }
 
+    /// How much a support skill heals or how strong its buff is, from the skill's attribute.
+    pub fn support_amount(&self, player: &Character) -> i32 {
+        let player_attributes = player.attributes.clone() + player.equipment.attribute();
+        let attribute = self.skill.attribute(&player_attributes) + self.action_experience_scaling();
+        match self.skill.support() {
+            Some(Support::Heal) => player.max_hp as i32 / 4 + attribute,
+            Some(Support::Buff(BuffKind::Armor)) => attribute * 2,
+            Some(Support::Buff(BuffKind::Ward)) => attribute * 3,
+            Some(Support::Buff(BuffKind::Dodge)) => attribute / 3,
+            Some(Support::Buff(BuffKind::Haste)) => 1 + attribute / 50,
+            Some(Support::Buff(BuffKind::Empower)) => 10 + attribute,
+            None => 0,
+        }
+    }
+
     pub fn action_experience_scaling(&self) -> i32 {
         self.level as i32
     }
@@ -647,20 +855,22 @@ mod test {
     use crate::character::Character;
     use crate::class::Classes::Paladin;
     use crate::enemy::Mob;
+    use crate::rng::CarrionRng;
     use crate::skill::SkillSet;
     use crate::unit::Attributes;
-    use rand::random;
+    use rand::Rng;
 
     #[test]
     fn slash_damage_within_range() {
         let me = Character::new("sdf".to_string(), 23, Paladin);
         let skill = crate::skill::Skill::Slash;
         let skill_set = SkillSet::new(skill);
-        let mob: Mob = random();
-        let enemy = mob.generate(me.level);
-        let damage = skill_set.act(&me, &enemy);
+        let mut rng = CarrionRng::new(1);
+        let mob: Mob = rng.gen();
+        let enemy = mob.generate(me.level, &mut rng);
+        let damage = skill_set.act(&me, &enemy, &mut rng);
         for _ in 0..100 {
-            assert!(damage.damage() < 250, "Damage was {:?}", damage);
+            assert!(damage.damage(&mut rng) < 250, "Damage was {:?}", damage);
         }
     }
 
@@ -680,25 +890,45 @@ mod test {
     fn enemy_damage() {
         let me = Character::new("sdf".to_string(), 23, Paladin);
         let orc = crate::enemy::Mob::Orc;
-        let mut enemy = orc.generate(me.level);
-        let (damage, _action) = enemy.action();
+        let mut rng = CarrionRng::new(2);
+        let mut enemy = orc.generate(me.level, &mut rng);
+        let (damage, _action) = enemy.action(&mut rng);
         for _n in 0..100 {
-            assert!(damage.damage() < 150, "Damage was {:?}", damage);
+            assert!(damage.damage(&mut rng) < 150, "Damage was {:?}", damage);
         }
         enemy.level = 60;
-        let (damage, _action) = enemy.action();
+        let (damage, _action) = enemy.action(&mut rng);
         for _n in 0..100 {
-            let dmg = damage.damage();
+            let dmg = damage.damage(&mut rng);
             assert!(dmg < 530, "Damage was {:?}", dmg);
             assert!(dmg > 100, "Damage was {:?}", dmg);
         }
 
         enemy.kind = crate::enemy::Mob::Eldragor;
-        let (damage, _action) = enemy.action();
+        let (damage, _action) = enemy.action(&mut rng);
         for _n in 0..100 {
-            let dmg = damage.damage();
+            let dmg = damage.damage(&mut rng);
             assert!(dmg < 1430, "Damage was {:?}", dmg);
             assert!(dmg > 200, "Damage was {:?}", dmg);
         }
     }
+
+    #[test]
+    fn mastered_pairs_discover_ascended_skills() {
+        use crate::skill::{discover, Skill, MASTERY_LEVEL};
+        use crate::ValidEnum;
+        use std::collections::HashMap;
+
+        let mut levels = HashMap::from([
+            (Skill::Slash, MASTERY_LEVEL),
+            (Skill::PowerStrike, MASTERY_LEVEL - 1),
+        ]);
+        assert!(discover(&levels, &[]).is_empty());
+        levels.insert(Skill::PowerStrike, MASTERY_LEVEL);
+        assert_eq!(discover(&levels, &[]), vec![Skill::Worldsplitter]);
+        assert!(discover(&levels, &[Skill::Worldsplitter]).is_empty());
+
+        assert!(!Skill::valid().contains("Worldsplitter"));
+        assert!(Skill::valid_for(&[Skill::Worldsplitter]).contains("Worldsplitter"));
+    }
 }
@@ -0,0 +1,180 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use crate::damage::UniqueDamageEffect;
+use crate::replay::Combatant;
+
+/// The lingering part of a `UniqueDamageEffect`. Every stack deals `tick` damage, or heals it
+/// for `Regenerate`, at the start of each round until `turns` runs out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct StatusEffect {
+    pub effect: UniqueDamageEffect,
+    pub stacks: u32,
+    pub turns: u32,
+    pub tick: i32,
+}
+
+impl StatusEffect {
+    /// Returns `None` for effects that only change the hit they came with.
+    pub fn new(effect: UniqueDamageEffect, tick: i32) -> Option<Self> {
+        use UniqueDamageEffect::*;
+        let turns = match effect {
+            Poison => 5,
+            Bleed => 3,
+            Burn => 3,
+            Shock => 2,
+            Curse => 4,
+            Regenerate => 4,
+            _ => return None,
+        };
+        Some(Self {
+            effect,
+            stacks: 1,
+            turns,
+            tick: tick.max(0),
+        })
+    }
+
+    pub fn with_stacks(mut self, stacks: u32) -> Self {
+        self.stacks = stacks.clamp(1, self.max_stacks());
+        self
+    }
+
+    pub fn max_stacks(&self) -> u32 {
+        use UniqueDamageEffect::*;
+        match self.effect {
+            Poison => 5,
+            Bleed => 10,
+            Curse => 3,
+            Shock => 2,
+            _ => 1,
+        }
+    }
+
+    /// Share of extra damage the target takes from hits while the effect is active.
+    pub fn vulnerability(&self) -> f64 {
+        use UniqueDamageEffect::*;
+        match self.effect {
+            Shock => 0.5 * self.stacks as f64,
+            Curse => 0.25 * self.stacks as f64,
+            _ => 0.0,
+        }
+    }
+
+    pub fn heals(&self) -> bool {
+        self.effect == UniqueDamageEffect::Regenerate
+    }
+
+    pub fn amount(&self) -> i32 {
+        self.tick.saturating_mul(self.stacks as i32)
+    }
+}
+
+impl Display for StatusEffect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.effect)?;
+        if self.stacks > 1 {
+            write!(f, " x{}", self.stacks)?;
+        }
+        write!(f, " ({} turns)", self.turns)
+    }
+}
+
+/// Every effect currently active on a character or an enemy.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatusEffects {
+    effects: Vec<StatusEffect>,
+}
+
+impl StatusEffects {
+    /// Adds the effect, or stacks it onto the active one and refreshes its duration.
+    pub fn apply(&mut self, status: StatusEffect) {
+        match self.effects.iter_mut().find(|s| s.effect == status.effect) {
+            Some(active) => {
+                active.stacks = (active.stacks + status.stacks).min(active.max_stacks());
+                active.turns = active.turns.max(status.turns);
+                active.tick = active.tick.max(status.tick);
+            }
+            None => self.effects.push(status),
+        }
+    }
+
+    /// Counts every effect down by a turn and returns them as they were when they ticked.
+    pub fn tick(&mut self) -> Vec<StatusEffect> {
+        let ticked = self.effects.clone();
+        self.effects.iter_mut().for_each(|s| s.turns -= 1);
+        self.effects.retain(|s| s.turns > 0);
+        ticked
+    }
+
+    pub fn vulnerability(&self) -> f64 {
+        self.effects.iter().map(StatusEffect::vulnerability).sum()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &StatusEffect> {
+        self.effects.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.effects.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.effects.clear();
+    }
+}
+
+impl Display for StatusEffects {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let effects: Vec<String> = self.effects.iter().map(|s| s.to_string()).collect();
+        write!(f, "{}", effects.join(", "))
+    }
+}
+
+/// One effect ticking on one combatant. `amount` is what was actually dealt or healed.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StatusTick {
+    pub target: Combatant,
+    pub status: StatusEffect,
+    pub amount: i32,
+}
+
+impl Display for StatusTick {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let verb = if self.status.heals() {
+            "healed"
+        } else {
+            "took"
+        };
+        write!(
+            f,
+            "{} {} {} from {}",
+            self.target, verb, self.amount, self.status
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{StatusEffect, StatusEffects};
+    use crate::damage::UniqueDamageEffect;
+
+    #[test]
+    fn effects_stack_and_expire() {
+        let mut effects = StatusEffects::default();
+        let poison = StatusEffect::new(UniqueDamageEffect::Poison, 3).unwrap();
+        for _ in 0..7 {
+            effects.apply(poison);
+        }
+        let active = *effects.iter().next().unwrap();
+        assert_eq!(active.stacks, active.max_stacks());
+        assert_eq!(active.amount(), 3 * active.max_stacks() as i32);
+
+        for _ in 0..poison.turns {
+            assert_eq!(effects.tick().len(), 1);
+        }
+        assert!(effects.is_empty());
+        assert!(StatusEffect::new(UniqueDamageEffect::Death, 1).is_none());
+    }
+}
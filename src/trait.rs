@@ -17,6 +17,22 @@ pub enum TraitMutation {
     ActionBonus(i32),
     CriticalMultiplier(f64),
     CriticalChance(f64),
+
+    /// Share of the damage dealt healed back.
+    Lifesteal(f64),
+    /// Share of the damage taken dealt back to the attacker.
+    Thorns(f64),
+    /// Share of max HP healed at the start of every round.
+    Regeneration(f64),
+    /// Damage multiplier gained at or below a share of max HP.
+    LastStand(f64, f64),
+
+    /// Share of extra gold from killed enemies.
+    GoldBonus(f64),
+    /// Share of extra gold from selling items.
+    SellBonus(f64),
+    /// Chance of an extra drop from killed enemies, a negative chance loses their drop.
+    ItemFind(f64),
 }
 
 #[derive(Clone, Default)]
@@ -28,6 +44,13 @@ pub struct TraitMutations {
     suppress: HashMap<ResistCategories, i32>,
     armor: i32,
     actions: i32,
+    lifesteal: f64,
+    thorns: f64,
+    regeneration: f64,
+    last_stand: Option<(f64, f64)>,
+    gold: f64,
+    sell: f64,
+    item_find: f64,
 }
 
 impl TraitMutations {
@@ -41,6 +64,28 @@ impl TraitMutations {
         }
     }
 
+    fn set_hook(&mut self, tr: TraitMutation) {
+        match tr {
+            TraitMutation::Lifesteal(e) => self.lifesteal += e,
+            TraitMutation::Thorns(e) => self.thorns += e,
+            TraitMutation::Regeneration(e) => self.regeneration += e,
+            TraitMutation::LastStand(threshold, bonus) => {
+                let (current, current_bonus) = self.last_stand.unwrap_or((0.0, 0.0));
+                self.last_stand = Some((current.max(threshold), current_bonus + bonus));
+            }
+            _ => {}
+        }
+    }
+
+    fn set_loot(&mut self, tr: TraitMutation) {
+        match tr {
+            TraitMutation::GoldBonus(e) => self.gold += e,
+            TraitMutation::SellBonus(e) => self.sell += e,
+            TraitMutation::ItemFind(e) => self.item_find += e,
+            _ => {}
+        }
+    }
+
     fn set_dodge(&mut self, tr: TraitMutation) {
         match tr {
             TraitMutation::FlatIncrease(e) => self.dodge += e,
@@ -87,6 +132,44 @@ impl TraitMutations {
         self.actions
     }
 
+    /// HP healed after landing a hit for `damage`.
+    pub fn on_hit(&self, damage: i32) -> i32 {
+        (damage.max(0) as f64 * self.lifesteal) as i32
+    }
+
+    /// Damage reflected at the attacker after taking `damage`.
+    pub fn on_damage_taken(&self, damage: i32) -> i32 {
+        (damage.max(0) as f64 * self.thorns) as i32
+    }
+
+    /// HP healed at the start of every round.
+    pub fn regeneration(&self, max_hp: u32) -> i32 {
+        (max_hp as f64 * self.regeneration) as i32
+    }
+
+    /// Damage multiplier bonus while `hp` is at or below the last stand threshold.
+    pub fn last_stand(&self, hp: i32, max_hp: u32) -> f64 {
+        match self.last_stand {
+            Some((threshold, bonus)) if hp as f64 <= max_hp as f64 * threshold => bonus,
+            _ => 0.0,
+        }
+    }
+
+    /// Gold paid out for an enemy worth `gold`.
+    pub fn gold(&self, gold: u64) -> u64 {
+        (gold as f64 * (1.0 + self.gold).max(0.0)) as u64
+    }
+
+    /// Gold paid for items that sold for `gold`.
+    pub fn sell_price(&self, gold: u64) -> u64 {
+        (gold as f64 * (1.0 + self.sell).max(0.0)) as u64
+    }
+
+    /// Chance of an extra drop, or of losing the drop when negative.
+    pub fn item_find(&self) -> f64 {
+        self.item_find.clamp(-1.0, 1.0)
+    }
+
     pub fn get_armor(&self) -> i32 {
         self.armor
     }
@@ -282,6 +365,10 @@ pub enum CharacterTraits {
     Greedy,
     Keen,
     Energetic,
+    Vampiric,
+    Thorny,
+    Regenerative,
+    Desperate,
 }
 
 impl CharacterTraits {
@@ -297,7 +384,10 @@ impl CharacterTraits {
             CharacterTraits::Genius => {
                 character_attributes.intelligence += 3;
             }
-            CharacterTraits::Lucky => {}
+            CharacterTraits::Lucky => {
+                character_attributes.dexterity += 1;
+                character_attributes.charisma += 1;
+            }
             CharacterTraits::FolkHero => {
                 character_attributes.charisma += 2;
                 character_attributes.constitution += 2;
@@ -315,15 +405,47 @@ impl CharacterTraits {
                 character_attributes.constitution += 3;
                 character_attributes.intelligence += 5;
             }
-            CharacterTraits::Cursed => {}
-            CharacterTraits::Unlucky => {}
-            CharacterTraits::Righteous => {}
-            CharacterTraits::Greedy => {}
-            CharacterTraits::Keen => {}
-            CharacterTraits::Energetic => {}
+            CharacterTraits::Cursed => {
+                character_attributes.intelligence += 3;
+            }
+            CharacterTraits::Unlucky => {
+                character_attributes.constitution += 2;
+                character_attributes.wisdom += 2;
+            }
+            CharacterTraits::Righteous => {
+                character_attributes.wisdom += 2;
+                character_attributes.charisma += 1;
+            }
+            CharacterTraits::Greedy => {
+                character_attributes.charisma += 2;
+            }
+            CharacterTraits::Keen => {
+                character_attributes.dexterity += 2;
+                character_attributes.intelligence += 1;
+            }
+            CharacterTraits::Energetic => {
+                character_attributes.dexterity += 1;
+                character_attributes.constitution += 1;
+            }
+            CharacterTraits::Vampiric => {}
+            CharacterTraits::Thorny => {
+                character_attributes.constitution += 2;
+            }
+            CharacterTraits::Regenerative => {
+                character_attributes.constitution += 1;
+                character_attributes.wisdom += 1;
+            }
+            CharacterTraits::Desperate => {}
         }
     }
 
+    /// Takes back what `attribute_mutator` gave.
+    pub fn attribute_reverter(&self, character_attributes: &mut Attributes) {
+        let mut granted = Attributes::zero();
+        self.attribute_mutator(&mut granted);
+        *character_attributes = character_attributes.clone() - granted;
+    }
+
     pub fn apply_traits(traits: &HashSet<CharacterTraits>) -> TraitMutations {
         let mut trait_mutations = TraitMutations::new();
         for tr in traits {
@@ -364,7 +486,8 @@ impl CharacterTraits {
                         TraitMutation::MultiplicativeBonus(1.8),
                     );
                     trait_mutations
-                        .set_damage(DamageType::Universal, TraitMutation::CriticalChance(0.15))
+                        .set_damage(DamageType::Universal, TraitMutation::CriticalChance(0.15));
+                    trait_mutations.set_loot(TraitMutation::ItemFind(0.15));
                 }
                 CharacterTraits::FolkHero => {
                     let mutations = vec![
@@ -409,14 +532,19 @@ impl CharacterTraits {
                 }
 
                 CharacterTraits::Cursed => {
-                    trait_mutations.set_suppress(
-                        ResistCategories::Universal,
-                        TraitMutation::FlatDecrease(2000),
+                    trait_mutations.set_damage(
+                        DamageType::Universal,
+                        TraitMutation::MultiplicativeBonus(-0.15),
                     );
+                    trait_mutations.set_loot(TraitMutation::ItemFind(0.35));
+                    trait_mutations.set_loot(TraitMutation::GoldBonus(0.25));
+                }
+                CharacterTraits::Unlucky => {
                     trait_mutations
-                        .set_damage(DamageType::Universal, TraitMutation::FlatIncrease(250));
+                        .set_damage(DamageType::Universal, TraitMutation::CriticalChance(-0.05));
+                    trait_mutations.set_dodge(TraitMutation::FlatDecrease(100));
+                    trait_mutations.set_loot(TraitMutation::ItemFind(-0.1));
                 }
-                CharacterTraits::Unlucky => {}
                 CharacterTraits::Righteous => {
                     let traits = vec![
                         TraitMutation::FlatIncrease(200),
@@ -424,9 +552,9 @@ impl CharacterTraits {
                         TraitMutation::CriticalMultiplier(0.15),
                     ];
                     let alignments = vec![
+                        Alignment::LawfulEvil,
+                        Alignment::NeutralEvil,
                         Alignment::ChaoticEvil,
-                        Alignment::ChaoticGood,
-                        Alignment::ChaoticNeutral,
                     ];
                     for m in traits {
                         for a in &alignments {
@@ -442,7 +570,9 @@ impl CharacterTraits {
                     trait_mutations.set_alignment_damage(
                         Alignment::LawfulGood,
                         TraitMutation::FlatIncrease(1000),
-                    )
+                    );
+                    trait_mutations.set_loot(TraitMutation::GoldBonus(0.5));
+                    trait_mutations.set_loot(TraitMutation::SellBonus(0.5));
                 }
                 CharacterTraits::Keen => {
                     trait_mutations
@@ -455,6 +585,18 @@ impl CharacterTraits {
                 CharacterTraits::Energetic => {
                     trait_mutations.set_actions(TraitMutation::ActionBonus(3));
                 }
+                CharacterTraits::Vampiric => {
+                    trait_mutations.set_hook(TraitMutation::Lifesteal(0.15));
+                }
+                CharacterTraits::Thorny => {
+                    trait_mutations.set_hook(TraitMutation::Thorns(0.25));
+                }
+                CharacterTraits::Regenerative => {
+                    trait_mutations.set_hook(TraitMutation::Regeneration(0.03));
+                }
+                CharacterTraits::Desperate => {
+                    trait_mutations.set_hook(TraitMutation::LastStand(0.25, 0.5));
+                }
             }
         }
 
@@ -489,4 +631,47 @@ mod test {
         );
         assert!(damage.damage == 15);
     }
+
+    #[test]
+    fn hp_traits_hook_into_hits() {
+        let traits = HashSet::from([
+            CharacterTraits::Vampiric,
+            CharacterTraits::Thorny,
+            CharacterTraits::Regenerative,
+            CharacterTraits::Desperate,
+        ]);
+        let trait_mutations = CharacterTraits::apply_traits(&traits);
+        assert_eq!(trait_mutations.on_hit(100), 15);
+        assert_eq!(trait_mutations.on_hit(-5), 0);
+        assert_eq!(trait_mutations.on_damage_taken(100), 25);
+        assert_eq!(trait_mutations.regeneration(200), 6);
+        assert_eq!(trait_mutations.last_stand(50, 200), 0.5);
+        assert_eq!(trait_mutations.last_stand(51, 200), 0.0);
+
+        let none = CharacterTraits::apply_traits(&HashSet::new());
+        assert_eq!(none.on_hit(100), 0);
+        assert_eq!(none.last_stand(0, 200), 0.0);
+    }
+
+    #[test]
+    fn every_trait_grants_something() {
+        use CharacterTraits::*;
+        for tr in [Lucky, Cursed, Unlucky, Righteous, Greedy, Keen, Energetic] {
+            let mut attributes = Attributes::zero();
+            tr.attribute_mutator(&mut attributes);
+            assert!(attributes.sum() > 0, "{:?} grants no attributes", tr);
+        }
+
+        let greedy = CharacterTraits::apply_traits(&HashSet::from([Greedy]));
+        assert_eq!(greedy.gold(100), 150);
+        assert_eq!(greedy.sell_price(10), 15);
+        let lucky = CharacterTraits::apply_traits(&HashSet::from([Lucky]));
+        assert!(lucky.item_find() > 0.0);
+        let unlucky = CharacterTraits::apply_traits(&HashSet::from([Unlucky]));
+        assert!(unlucky.item_find() < 0.0);
+        assert!(unlucky.get_dodge() < 0);
+        let cursed = CharacterTraits::apply_traits(&HashSet::from([Cursed]));
+        assert!(cursed.item_find() > lucky.item_find());
+        assert!(cursed.get_elemental_damage(DamageType::Physical).multiplier < 0.0);
+    }
 }
@@ -9,6 +9,16 @@ use std::fmt::Display;
 use std::fmt::Formatter;
 use std::ops::{Add, AddAssign, Sub};
 
+/// Names of the attributes as `Attributes::get` and `Attributes::add` take them.
+pub const ATTRIBUTES: [&str; 6] = [
+    "strength",
+    "dexterity",
+    "constitution",
+    "intelligence",
+    "wisdom",
+    "charisma",
+];
+
 #[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq, Eq, Hash)]
 pub struct Attributes {
     pub(crate) strength: i32,
@@ -200,7 +210,6 @@ impl From<&Mob> for Attributes {
 
 impl From<&Classes> for Attributes {
     fn from(classes: &Classes) -> Self {
-        let stat = AttributeScaling::scaling(classes).expect("Invalid class");
         let base = Self {
             strength: 10,
             dexterity: 10,
@@ -209,68 +218,63 @@ impl From<&Classes> for Attributes {
             wisdom: 10,
             charisma: 10,
         };
+        base + Attributes::profile(classes)
+    }
+}
 
+impl Attributes {
+    /// What a class's `#[stat]` adds on top of the base 10s, also granted again on promotion.
+    pub(crate) fn profile(class: &Classes) -> Self {
+        let stat = AttributeScaling::scaling(class).expect("Invalid class");
         match stat.to_owned().as_str() {
-            "strength" => {
-                base + Self {
-                    strength: 8,
-                    dexterity: 1,
-                    constitution: 5,
-                    intelligence: 0,
-                    wisdom: 0,
-                    charisma: 0,
-                }
-            }
-            "dexterity" => {
-                base + Self {
-                    strength: 1,
-                    dexterity: 10,
-                    constitution: 3,
-                    intelligence: 0,
-                    wisdom: 0,
-                    charisma: 0,
-                }
-            }
-            "constitution" => {
-                base + Self {
-                    strength: 5,
-                    dexterity: 0,
-                    constitution: 8,
-                    intelligence: 0,
-                    wisdom: 3,
-                    charisma: 3,
-                }
-            }
-            "intelligence" => {
-                base + Self {
-                    strength: 0,
-                    dexterity: 0,
-                    constitution: 0,
-                    intelligence: 8,
-                    wisdom: 5,
-                    charisma: 0,
-                }
-            }
-            "wisdom" => {
-                base + Self {
-                    strength: 0,
-                    dexterity: 0,
-                    constitution: 0,
-                    intelligence: 3,
-                    wisdom: 10,
-                    charisma: 5,
-                }
-            }
-            "charisma" => {
-                base + Self {
-                    strength: 5,
-                    dexterity: 0,
-                    constitution: 5,
-                    intelligence: 0,
-                    wisdom: 0,
-                    charisma: 8,
-                }
-            }
+            "strength" => Self {
+                strength: 8,
+                dexterity: 1,
+                constitution: 5,
+                intelligence: 0,
+                wisdom: 0,
+                charisma: 0,
+            },
+            "dexterity" => Self {
+                strength: 1,
+                dexterity: 10,
+                constitution: 3,
+                intelligence: 0,
+                wisdom: 0,
+                charisma: 0,
+            },
+            "constitution" => Self {
+                strength: 5,
+                dexterity: 0,
+                constitution: 8,
+                intelligence: 0,
+                wisdom: 3,
+                charisma: 3,
+            },
+            "intelligence" => Self {
+                strength: 0,
+                dexterity: 0,
+                constitution: 0,
+                intelligence: 8,
+                wisdom: 5,
+                charisma: 0,
+            },
+            "wisdom" => Self {
+                strength: 0,
+                dexterity: 0,
+                constitution: 0,
+                intelligence: 3,
+                wisdom: 10,
+                charisma: 5,
+            },
+            "charisma" => Self {
+                strength: 5,
+                dexterity: 0,
+                constitution: 5,
+                intelligence: 0,
+                wisdom: 0,
+                charisma: 8,
+            },
             _ => panic!("Invalid class"),
         }
     }
@@ -289,6 +293,33 @@ pub enum Alignment {
     ChaoticEvil,
 }
 
+impl Alignment {
+    /// Good is 1, evil -1.
+    fn morality(&self) -> i8 {
+        use Alignment::*;
+        match self {
+            LawfulGood | NeutralGood | ChaoticGood => 1,
+            LawfulNeutral | TrueNeutral | ChaoticNeutral => 0,
+            LawfulEvil | NeutralEvil | ChaoticEvil => -1,
+        }
+    }
+
+    /// Lawful is 1, chaotic -1.
+    fn order(&self) -> i8 {
+        use Alignment::*;
+        match self {
+            LawfulGood | LawfulNeutral | LawfulEvil => 1,
+            NeutralGood | TrueNeutral | NeutralEvil => 0,
+            ChaoticGood | ChaoticNeutral | ChaoticEvil => -1,
+        }
+    }
+
+    /// Good opposes evil and lawful opposes chaotic.
+    pub fn opposes(&self, other: Alignment) -> bool {
+        self.morality() * other.morality() < 0 || self.order() * other.order() < 0
+    }
+}
+
 impl From<&str> for Alignment {
     fn from(s: &str) -> Self {
         match s.to_lowercase().as_str() {
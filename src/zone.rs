@@ -0,0 +1,163 @@
+use eris_macro::{ErisDisplayEmoji, ErisValidEnum};
+use rand::distributions::WeightedIndex;
+use rand::prelude::Distribution;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use strum::EnumIter;
+
+use crate::enemy::{Enemy, Mob};
+
+/// Where a character hunts. Every zone has its own weighted mob table, a level offset for the
+/// enemies it spawns, a level band to enter it and multipliers on the gold and experience they
+/// drop. The Wilds keep the old behaviour of any mob at the character's level.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    Deserialize,
+    ErisValidEnum,
+    ErisDisplayEmoji,
+    EnumIter,
+)]
+pub enum Zone {
+    #[default]
+    #[emoji("🌾")]
+    Wilds,
+    #[emoji("🌲")]
+    Woods,
+    #[emoji("🕳️")]
+    Caves,
+    #[emoji("🏟️")]
+    Arena,
+    #[emoji("🌋")]
+    Badlands,
+}
+
+impl Zone {
+    /// Mobs and how often they show up, empty means every mob weighted by its grade.
+    pub fn mobs(&self) -> &'static [(Mob, u32)] {
+        use Mob::*;
+        match self {
+            Zone::Wilds => &[],
+            Zone::Woods => &[(Orc, 5), (Goblin, 5), (Elf, 2)],
+            Zone::Caves => &[(KingSlime, 4), (Drow, 3), (Troll, 3), (Bomb, 1)],
+            Zone::Arena => &[(Gladiator, 5), (Troll, 2), (Orc, 1)],
+            Zone::Badlands => &[
+                (Lich, 4),
+                (NeuronThief, 4),
+                (Behemoth, 2),
+                (Dreadmaw, 2),
+                (Eldragor, 1),
+            ],
+        }
+    }
+
+    pub fn level_offset(&self) -> i32 {
+        match self {
+            Zone::Wilds => 0,
+            Zone::Woods => -2,
+            Zone::Caves => 2,
+            Zone::Arena => 5,
+            Zone::Badlands => 10,
+        }
+    }
+
+    pub fn min_level(&self) -> u32 {
+        match self {
+            Zone::Wilds | Zone::Woods => 1,
+            Zone::Caves => 5,
+            Zone::Arena => 10,
+            Zone::Badlands => 25,
+        }
+    }
+
+    pub fn gold_multiplier(&self) -> f64 {
+        match self {
+            Zone::Wilds => 1.0,
+            Zone::Woods => 0.75,
+            Zone::Caves => 1.25,
+            Zone::Arena => 2.0,
+            Zone::Badlands => 1.5,
+        }
+    }
+
+    pub fn experience_multiplier(&self) -> f64 {
+        match self {
+            Zone::Wilds => 1.0,
+            Zone::Woods => 0.75,
+            Zone::Caves => 1.25,
+            Zone::Arena => 1.0,
+            Zone::Badlands => 2.0,
+        }
+    }
+
+    pub fn can_enter(&self, level: u32) -> bool {
+        level >= self.min_level()
+    }
+
+    /// Level of the enemies spawned here for a character of `level`.
+    pub fn level(&self, level: u32) -> u32 {
+        level.saturating_add_signed(self.level_offset()).max(1)
+    }
+
+    pub fn mob(&self, rng: &mut impl Rng) -> Mob {
+        let mobs = self.mobs();
+        if mobs.is_empty() {
+            return rng.gen();
+        }
+        let weights = WeightedIndex::new(mobs.iter().map(|(_, weight)| *weight))
+            .expect("Zone mob weights are invalid");
+        mobs[weights.sample(rng)].0
+    }
+
+    /// Picks a mob from the zone's table and generates it.
+    pub fn enemy(&self, level: u32, rng: &mut impl Rng) -> Enemy {
+        self.mob(rng).generate_in(*self, level, rng)
+    }
+
+    pub fn description(&self) -> String {
+        let mobs = match self.mobs() {
+            [] => "Anything".to_string(),
+            mobs => mobs
+                .iter()
+                .map(|(mob, _)| mob.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+        };
+        format!(
+            "{}: level {}+, enemies {:+} levels, {}x gold, {}x experience - {}",
+            self,
+            self.min_level(),
+            self.level_offset(),
+            self.gold_multiplier(),
+            self.experience_multiplier(),
+            mobs
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Zone;
+    use crate::rng::CarrionRng;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn zones_spawn_their_own_mobs() {
+        let mut rng = CarrionRng::new(11);
+        for zone in Zone::iter().filter(|zone| !zone.mobs().is_empty()) {
+            for _ in 0..20 {
+                let enemy = zone.enemy(10, &mut rng);
+                assert!(zone.mobs().iter().any(|(mob, _)| *mob == enemy.kind));
+                assert_eq!(enemy.level, zone.level(10));
+            }
+        }
+        assert_eq!(Zone::Woods.level(1), 1);
+        assert!(!Zone::Badlands.can_enter(10));
+    }
+}
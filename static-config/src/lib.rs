@@ -52,6 +52,25 @@ pub struct IndividualItem {
     pub(crate) action: Option<u32>,
     pub(crate) rarity: String,
     pub(crate) points: Option<u64>,
+    pub(crate) set: Option<String>,
+}
+
+/// A named set of items, defined in `items/sets/`. Each bonus applies once the character wears
+/// at least `pieces` different items of the set.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ItemSet {
+    pub(crate) name: String,
+    pub(crate) bonuses: Vec<SetBonus>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct SetBonus {
+    pub(crate) pieces: usize,
+    pub(crate) armor: Option<i32>,
+    pub(crate) evasion: Option<i32>,
+    pub(crate) resistance: Option<HashMap<String, i32>>,
+    pub(crate) damage: Option<HashMap<String, i32>>,
+    pub(crate) action: Option<u32>,
 }
 
 pub enum ResistCategories {
@@ -65,14 +84,21 @@ pub enum ResistCategories {
 
 pub fn parse_items() -> std::io::Result<String> {
     let items_directories = "items/";
+    let sets_directory = "items/sets";
     let mut items = Vec::new();
+    let mut sets = Vec::new();
     for entry in WalkDir::new(items_directories)
         .follow_links(true)
         .into_iter()
         .filter_map(|e| e.ok())
     {
         let path = entry.path();
-        if path.is_file() {
+        if path.is_file() && path.starts_with(sets_directory) {
+            let file = File::open(path)?;
+            let set: ItemSet = serde_yaml::from_reader(file)
+                .expect(&format!("Failed to parse item set, {}", path.display()));
+            sets.push(set);
+        } else if path.is_file() {
             let file = File::open(path)?;
             let item: IndividualItem = serde_yaml::from_reader(file)
                 .expect(&format!("Failed to parse item, {}", path.display()));
@@ -80,11 +106,20 @@ pub fn parse_items() -> std::io::Result<String> {
         }
     }
 
+    for item in &items {
+        if let Some(set) = &item.set {
+            if !sets.iter().any(|s| &s.name == set) {
+                panic!("{} belongs to the unknown item set {}", item.name, set);
+            }
+        }
+    }
+
     let mut source_code = base_source();
     for item in &items {
         source_code.push_str(&struct_conversion(&item));
     }
     source_code.push_str(&items_impls(&items));
+    source_code.push_str(&sets_impls(&sets));
 
     Ok(source_code)
 }
@@ -106,15 +141,82 @@ fn items_impls(struct_vec: &Vec<IndividualItem>) -> String {
         let attempts = grade as u64;
         for _ in 0..attempts {
             if rng.gen_bool(drop_probability.abs()) {
-                items.insert(ItemsWeHave::generate_random_item().expect(\"Failed to generate item\"));
+                let item = ItemsWeHave::generate_random_item().expect(\"Failed to generate item\");
+                if !item.dungeon_only() {
+                    items.insert(item);
+                }
             };
         }
         items.into_iter().collect()
     }");
+    let dungeon_loot: Vec<String> = struct_vec
+        .iter()
+        .filter(|st| dungeon_only(st))
+        .map(|st| format!("ItemsWeHave::{}", st.name.to_pascal_case()))
+        .collect();
+    source_code.push_str(&format!(
+        "\n\tpub fn dungeon_only(&self) -> bool {{\n\t\tmatches!(*self, {})\n\t}}\n",
+        dungeon_loot.join(" | ")
+    ));
+    source_code.push_str(&format!(
+        "\tpub fn dungeon_loot() -> Vec<ItemsWeHave> {{\n\t\tvec![{}]\n\t}}\n",
+        dungeon_loot.join(", ")
+    ));
     source_code.push('}');
     source_code
 }
 
+/// Legendary and rarer items only drop from cleared dungeon runs.
+fn dungeon_only(item: &IndividualItem) -> bool {
+    matches!(
+        item.rarity.as_str(),
+        "Legendary" | "Artifact" | "Wondrous" | "Unique"
+    )
+}
+
+fn sets_impls(sets: &Vec<ItemSet>) -> String {
+    let mut source_code = String::new();
+    source_code
+        .push_str("#[derive(Serialize, Deserialize, Hash, PartialEq, Clone, Copy, Debug, Eq)]\n");
+    source_code.push_str("pub enum ItemSet {\n");
+    for set in sets {
+        source_code.push_str(&format!("\t{},\n", set.name.to_pascal_case()));
+    }
+    source_code.push_str("}\n");
+    source_code.push_str("impl ItemSet {\n");
+    source_code.push_str("\tpub fn name(&self) -> &'static str {\n\t\tmatch *self {\n");
+    for set in sets {
+        source_code.push_str(&format!(
+            "\t\t\tItemSet::{} => \"{}\",\n",
+            set.name.to_pascal_case(),
+            set.name
+        ));
+    }
+    source_code.push_str("\t\t}\n\t}\n");
+    source_code.push_str("\tpub fn bonuses(&self) -> Vec<SetBonus> {\n\t\tmatch *self {\n");
+    for set in sets {
+        source_code.push_str(&format!(
+            "\t\t\tItemSet::{} => vec![",
+            set.name.to_pascal_case()
+        ));
+        for bonus in &set.bonuses {
+            source_code.push_str(&format!(
+                "SetBonus {{ pieces: {}, armor: {}, dodge: {}, resistance: {}, damage: {}, action: {} }},",
+                bonus.pieces,
+                bonus.armor.unwrap_or_default(),
+                bonus.evasion.unwrap_or_default(),
+                hash_map_mapping(bonus.resistance.clone(), "ResistCategories".to_string()),
+                hash_map_mapping(bonus.damage.clone(), "DamageType".to_string()),
+                bonus.action.unwrap_or_default()
+            ));
+        }
+        source_code.push_str("],\n");
+    }
+    source_code.push_str("\t\t}\n\t}\n");
+    source_code.push_str("}\n");
+    source_code
+}
+
 fn struct_conversion(st: &IndividualItem) -> String {
     let mut source_code = String::new();
     let struct_name = st.name.to_pascal_case();
@@ -154,6 +256,13 @@ fn struct_conversion(st: &IndividualItem) -> String {
     source_code.push_str(&format!("rarity: Rarity::{},", st.rarity));
     source_code.push_str("\n\t\t\t");
     source_code.push_str(&format!("points: {},", st.points.unwrap_or_default()));
+    source_code.push_str("\n\t\t\t");
+    match &st.set {
+        Some(set) => {
+            source_code.push_str(&format!("set: Some(ItemSet::{}),", set.to_pascal_case()))
+        }
+        None => source_code.push_str("set: None,"),
+    }
     source_code.push_str("\n\t\t");
     source_code.push_str("}\n");
     source_code.push_str("\t}\n");
@@ -187,6 +296,7 @@ fn base_source() -> String {
     source_code.push_str("use crate::item::EquipmentSlot;\n");
     source_code.push_str("use std::collections::{HashMap, HashSet};\n");
     source_code.push_str("use crate::item::Rarity;\n");
+    source_code.push_str("use crate::item::SetBonus;\n");
     source_code.push_str("use crate::damage::DamageType;\n");
     source_code.push_str("use crate::damage::ResistCategories;\n");
     source_code.push_str("use eris_macro::ErisConstructedTemplate;\n");